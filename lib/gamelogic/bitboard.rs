@@ -0,0 +1,148 @@
+use super::Side;
+use super::pieces::PieceType;
+
+/// A set of squares packed into a u64 where bit 0 = a1, bit 7 = h1, and bit 63 = h8.
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+pub const RANK_1: Bitboard = 0xFF;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+pub const DARK_SQUARES: Bitboard = 0xAA55_AA55_AA55_AA55;  // a1 is a dark square
+pub const LIGHT_SQUARES: Bitboard = !DARK_SQUARES;
+
+// Ray directions as (column step, row step). The first four increase the square index, the last four decrease it.
+const DIRECTIONS: [(i64, i64); 8] = [(0, 1), (1, 1), (1, 0), (-1, 1), (0, -1), (-1, -1), (-1, 0), (1, -1)];
+const ROOK_DIRECTIONS: [usize; 4] = [0, 2, 4, 6];
+const BISHOP_DIRECTIONS: [usize; 4] = [1, 3, 5, 7];
+
+lazy_static! {
+    static ref KNIGHT_ATTACKS: [Bitboard; 64] = build_step_table(&[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)]);
+    static ref KING_ATTACKS: [Bitboard; 64] = build_step_table(&[(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)]);
+    static ref PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+        build_step_table(&[(-1, 1), (1, 1)]),
+        build_step_table(&[(-1, -1), (1, -1)]),
+    ];
+    // RAYS[direction][square] holds every square from (but not including) the square to the edge of the board
+    static ref RAYS: [[Bitboard; 64]; 8] = {
+        let mut rays = [[EMPTY; 64]; 8];
+        for (direction, (col_step, row_step)) in DIRECTIONS.iter().enumerate() {
+            for (square, ray) in rays[direction].iter_mut().enumerate() {
+                let (mut col, mut row) = (square as i64 % 8, square as i64 / 8);
+                loop {
+                    col += col_step;
+                    row += row_step;
+                    if !(0..8).contains(&col) || !(0..8).contains(&row) {
+                        break;
+                    }
+                    *ray |= 1 << (row * 8 + col);
+                }
+            }
+        }
+        rays
+    };
+}
+
+/// Precompute the attack set of a non-sliding piece for every square given its list of (column, row) jumps.
+fn build_step_table(steps: &[(i64, i64)]) -> [Bitboard; 64] {
+    let mut table = [EMPTY; 64];
+    for (square, attacks) in table.iter_mut().enumerate() {
+        let (col, row) = (square as i64 % 8, square as i64 / 8);
+        for (col_step, row_step) in steps {
+            let (new_col, new_row) = (col + col_step, row + row_step);
+            if (0..8).contains(&new_col) && (0..8).contains(&new_row) {
+                *attacks |= 1 << (new_row * 8 + new_col);
+            }
+        }
+    }
+    table
+}
+
+/// Convert a (column, row) position into a square index from 0 (a1) to 63 (h8)
+pub fn square_index(position: (usize, usize)) -> usize {
+    position.1 * 8 + position.0
+}
+
+/// Convert a square index from 0 (a1) to 63 (h8) back into a (column, row) position
+pub fn index_to_position(index: usize) -> (usize, usize) {
+    (index % 8, index / 8)
+}
+
+/// Get a bitboard with only the given position set
+pub fn square_mask(position: (usize, usize)) -> Bitboard {
+    1 << square_index(position)
+}
+
+pub fn side_index(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 1,
+    }
+}
+
+pub fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Rook => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+/// Iterate over the (column, row) positions of every square set in the bitboard, from a1 to h8
+pub fn positions(bitboard: Bitboard) -> impl Iterator<Item = (usize, usize)> {
+    let mut remaining = bitboard;
+    std::iter::from_fn(move || {
+        if remaining == EMPTY {
+            return None;
+        }
+        let index = remaining.trailing_zeros() as usize;
+        remaining &= remaining - 1;
+        Some(index_to_position(index))
+    })
+}
+
+pub fn knight_attacks(position: (usize, usize)) -> Bitboard {
+    KNIGHT_ATTACKS[square_index(position)]
+}
+
+pub fn king_attacks(position: (usize, usize)) -> Bitboard {
+    KING_ATTACKS[square_index(position)]
+}
+
+/// Squares attacked by a pawn of the given side standing on the position
+pub fn pawn_attacks(side: Side, position: (usize, usize)) -> Bitboard {
+    PAWN_ATTACKS[side_index(side)][square_index(position)]
+}
+
+/// Follow a ray until (and including) the first occupied square
+fn ray_attacks(direction: usize, square: usize, occupancy: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][square];
+    let blockers = ray & occupancy;
+    if blockers == EMPTY {
+        return ray;
+    }
+    // the first 4 directions increase the square index, so the nearest blocker is the lowest bit. Otherwise it's the highest.
+    let nearest = match direction < 4 {
+        true => blockers.trailing_zeros() as usize,
+        false => 63 - blockers.leading_zeros() as usize,
+    };
+    ray ^ RAYS[direction][nearest]
+}
+
+pub fn rook_attacks(position: (usize, usize), occupancy: Bitboard) -> Bitboard {
+    let square = square_index(position);
+    ROOK_DIRECTIONS.iter().fold(EMPTY, |attacks, direction| attacks | ray_attacks(*direction, square, occupancy))
+}
+
+pub fn bishop_attacks(position: (usize, usize), occupancy: Bitboard) -> Bitboard {
+    let square = square_index(position);
+    BISHOP_DIRECTIONS.iter().fold(EMPTY, |attacks, direction| attacks | ray_attacks(*direction, square, occupancy))
+}
+
+pub fn queen_attacks(position: (usize, usize), occupancy: Bitboard) -> Bitboard {
+    rook_attacks(position, occupancy) | bishop_attacks(position, occupancy)
+}
@@ -1,28 +1,50 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::collections::hash_map::DefaultHasher;
 use std::fmt::Display;
-use std::hash::Hash;
-use std::hash::Hasher;
 
 use super::ChessError;
+use super::bitboard::{self, Bitboard};
 use super::ChessMove;
 use super::GameEnd;
+use super::GameEndReason;
+use super::MoveFilter;
 use super::MoveType;
 use super::Side;
+use super::Variant;
 use super::index_pair_to_name;
 use super::name_to_index_pair;
-use super::pieces::{ChessPiece, PieceType};
+use super::pieces::{remove_self_checks, ChessPiece, PieceType};
+use super::render::BoardRenderer;
+use super::zobrist;
 
-use colored::*;
 use itertools::Itertools;
 
 #[derive(Clone, Debug)]
 pub struct ChessBoard {
-    pub squares: [[Option<ChessPiece>; 8]; 8], // 0,0 = a1, 7,7 = h8
+    squares: [[Option<ChessPiece>; 8]; 8], // 0,0 = a1, 7,7 = h8
+    pieces: [[Bitboard; 6]; 2],  // indexed by side and then piece type, see bitboard::side_index and bitboard::piece_index
+    occupancy: [Bitboard; 2],  // all squares occupied by each side
     pub state: BoardStateFlags,
-    board_state_counts: HashMap<u64, usize>,
-    pub move_list: Vec<ChessMove>
+    zobrist_key: u64,  // incrementally updated as pieces and state change, see zobrist_key()
+    board_state_counts: HashMap<u64, usize>,  // how many times each position (by Zobrist key) has occured
+    pub move_list: Vec<ChessMove>,
+    starting_squares: [[Option<ChessPiece>; 8]; 8],  // the position before any of the moves in move_list, so the game can be replayed
+    starting_state: BoardStateFlags,
+    pub variant: Variant,
+    castling_rook_columns: [[usize; 2]; 2]  // indexed by side and then queenside (0) or kingside (1), see get_castling_rook_column
+}
+
+/// Columns of the queenside and kingside rooks for each side which can castle in standard chess
+pub(crate) const STANDARD_CASTLING_ROOK_COLUMNS: [[usize; 2]; 2] = [[0, 7], [0, 7]];
+
+/// Everything needed to take back a move performed with `ChessBoard::make_move` and restore the board exactly.
+#[derive(Clone, Debug)]
+pub struct MoveUndo {
+    pub chess_move: ChessMove,
+    pub moved_piece: ChessPiece,  // the piece as it was before moving, e.g. still a Pawn for promotions
+    pub captured_piece: Option<ChessPiece>,
+    pub previous_state: BoardStateFlags,
+    pub previous_zobrist_key: u64
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -32,7 +54,9 @@ pub struct BoardStateFlags {
     pub black_castle_queenside: bool,
     pub black_castle_kingside: bool,
     pub en_passant_column: Option<usize>,
-    pub current_turn: Side
+    pub current_turn: Side,
+    pub halfmove_clock: usize,  // half-moves since the last capture or pawn move, for the fifty-move rule
+    pub fullmove_number: usize  // starts at 1 and goes up after every Black move
 }
 
 impl Default for BoardStateFlags {
@@ -43,12 +67,20 @@ impl Default for BoardStateFlags {
             black_castle_queenside: true,
             black_castle_kingside: true,
             en_passant_column: Default::default(),
-            current_turn: Default::default()
+            current_turn: Default::default(),
+            halfmove_clock: 0,
+            fullmove_number: 1
         }
     }
 }
 
 
+impl Default for ChessBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChessBoard {
     /// Create a ChessBoard using the standard setup.
     pub fn new() -> Self {
@@ -76,34 +108,105 @@ impl ChessBoard {
         squares[7][7] = Some(ChessPiece { position: (7,7), side: Side::Black, piece_type: PieceType::Rook});
 
         // setup pawn rows for both white and black
-        for col in 0..8 {
-            squares[col][1] = Some(ChessPiece { position: (col,1), side: Side::White, piece_type: PieceType::Pawn});
-            squares[col][6] = Some(ChessPiece { position: (col,6), side: Side::Black, piece_type: PieceType::Pawn});
+        for (col, column) in squares.iter_mut().enumerate() {
+            column[1] = Some(ChessPiece { position: (col,1), side: Side::White, piece_type: PieceType::Pawn});
+            column[6] = Some(ChessPiece { position: (col,6), side: Side::Black, piece_type: PieceType::Pawn});
         }
 
         // create initialized ChessBoard object and pass back to caller
-        ChessBoard {
-            squares,  // 2d array of columns and rows
-            state: BoardStateFlags { ..Default::default() },  // start with all flags false
-            board_state_counts: HashMap::new(),
-            move_list: Vec::new()
-        }
+        Self::new_with_squares(squares)
     }
 
     /// Create a Board object with the specified squares.
     pub fn new_with_squares(setup: [[Option<ChessPiece>; 8]; 8]) -> Self {
-        ChessBoard {
-            squares: setup,  // 2d array of columns and rows
-            state: BoardStateFlags { ..Default::default() },  // start with all flags false
+        Self::new_with_state(setup, BoardStateFlags { ..Default::default() }, Variant::Standard, STANDARD_CASTLING_ROOK_COLUMNS)
+    }
+
+    /// Create a Chess960 board using one of the 960 starting positions, numbered as per https://en.wikipedia.org/wiki/Fischer_random_chess_numbering_scheme
+    /// Position 518 is the standard setup.
+    pub fn new_chess960(position_index: usize) -> Result<Self, ChessError> {
+        if position_index >= 960 {
+            return Err(ChessError::InvalidArgument(format!("Chess960 position must be between 0-959 inclusive: '{}'", position_index)));
+        }
+        let mut back_row: [Option<PieceType>; 8] = Default::default();
+        let mut n = position_index;
+        // the bishops are always on opposite colored squares
+        back_row[(n % 4) * 2 + 1] = Some(PieceType::Bishop);
+        n /= 4;
+        back_row[(n % 4) * 2] = Some(PieceType::Bishop);
+        n /= 4;
+        // the queen and then the knights go on the remaining empty squares
+        let mut place_on_empty = |nth_empty: usize, piece_type: PieceType| {
+            let col = (0..8).filter(|col| back_row[*col].is_none()).nth(nth_empty).unwrap();
+            back_row[col] = Some(piece_type);
+        };
+        place_on_empty(n % 6, PieceType::Queen);
+        n /= 6;
+        // the position of the second knight is counted once the first is already placed
+        let (first_knight, second_knight) = [(0, 0), (0, 1), (0, 2), (0, 3), (1, 1), (1, 2), (1, 3), (2, 2), (2, 3), (3, 3)][n];
+        place_on_empty(first_knight, PieceType::Knight);
+        place_on_empty(second_knight, PieceType::Knight);
+        // then the king always goes between the two rooks
+        place_on_empty(0, PieceType::Rook);
+        place_on_empty(0, PieceType::King);
+        place_on_empty(0, PieceType::Rook);
+
+        let mut squares: [[Option<ChessPiece>; 8]; 8] = Default::default();
+        let mut rook_columns = Vec::new();
+        for (col, piece_type) in back_row.into_iter().enumerate() {
+            let piece_type = piece_type.unwrap();
+            if piece_type == PieceType::Rook {
+                rook_columns.push(col);
+            }
+            squares[col][0] = Some(ChessPiece { position: (col, 0), side: Side::White, piece_type });
+            squares[col][1] = Some(ChessPiece { position: (col, 1), side: Side::White, piece_type: PieceType::Pawn });
+            squares[col][6] = Some(ChessPiece { position: (col, 6), side: Side::Black, piece_type: PieceType::Pawn });
+            squares[col][7] = Some(ChessPiece { position: (col, 7), side: Side::Black, piece_type });
+        }
+        let rook_columns = [rook_columns[0], rook_columns[1]];
+        Ok(Self::new_with_state(squares, BoardStateFlags { ..Default::default() }, Variant::Chess960, [rook_columns, rook_columns]))
+    }
+
+    /// Create a Board object with the specified squares and state flags, building the bitboards from the squares.
+    pub(crate) fn new_with_state(setup: [[Option<ChessPiece>; 8]; 8], state: BoardStateFlags, variant: Variant, castling_rook_columns: [[usize; 2]; 2]) -> Self {
+        let mut board = ChessBoard {
+            squares: Default::default(),
+            pieces: Default::default(),
+            occupancy: Default::default(),
+            state,
+            zobrist_key: 0,
             board_state_counts: HashMap::new(),
-            move_list: Vec::new()
+            move_list: Vec::new(),
+            starting_squares: setup,
+            starting_state: state,
+            variant,
+            castling_rook_columns
+        };
+        for (col, column) in setup.iter().enumerate() {
+            for (row, square) in column.iter().enumerate() {
+                board.set_square((col, row), *square);
+            }
         }
+        board.zobrist_key ^= board.get_state_key();
+        // the starting position counts towards repetitions too
+        board.record_board_state();
+        board
     }
 
-    /// Parses a FEN string into a Board. It doesn't validate that the pieces make sense, e.g. that there's a King for each side.
+    /// Parses a FEN string into a Board. It doesn't validate that the pieces make sense, e.g. that there's a King for each side, use `from_forsyth_edwards_strict` for that.
     /// https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation
     /// https://www.chess.com/terms/fen-chess
     pub fn from_forsyth_edwards(fen_string: String) -> Result<Self, ChessError> {
+        Self::parse_forsyth_edwards(fen_string, Variant::Standard)
+    }
+
+    /// Parses a Chess960 FEN string into a Board. The castling rights can be either X-FEN (KQkq, or the rook's file if it's not the outermost rook) or Shredder-FEN (the rook's file, e.g. HAha).
+    /// https://en.wikipedia.org/wiki/X-FEN
+    pub fn from_forsyth_edwards_960(fen_string: String) -> Result<Self, ChessError> {
+        Self::parse_forsyth_edwards(fen_string, Variant::Chess960)
+    }
+
+    fn parse_forsyth_edwards(fen_string: String, variant: Variant) -> Result<Self, ChessError> {
         //
         // Split and validate FEN string that it matches the basic expected format.
         //
@@ -127,6 +230,8 @@ impl ChessBoard {
             black_castle_kingside: false,
             en_passant_column: None,
             current_turn: Side::White,
+            halfmove_clock: 0,
+            fullmove_number: 1,
         };
 
         //
@@ -151,7 +256,7 @@ impl ChessBoard {
                     if cur_col + num_empty > 8 {
                         return Err(ChessError::InvalidState(format!("FEN position string says there's more empty squares than possibly exist '{}': {}", char, row_str)));
                     }
-                    cur_col = cur_col + num_empty;
+                    cur_col += num_empty;
                     continue;
                 }
                 // determine current side, black pieces are lowercase
@@ -171,13 +276,11 @@ impl ChessBoard {
                     _ => return Err(ChessError::InvalidState(format!("FEN could not be parsed because character isn't recognized '{}': {}", char, fen_string)))
                 };
                 // set the square to the given chess piece
-                squares[cur_col][cur_row] = Some(ChessPiece { position: (cur_col, cur_row), side: piece_side, piece_type: piece_type });
-                cur_col = cur_col + 1;
+                squares[cur_col][cur_row] = Some(ChessPiece { position: (cur_col, cur_row), side: piece_side, piece_type });
+                cur_col += 1;
             }
             // As long as we're not at row 0, subtract -1 as we start at the last row and work backwards with FEN notation
-            if cur_row != 0 {
-                cur_row = cur_row - 1;
-            }
+            cur_row = cur_row.saturating_sub(1);
         }
 
         //
@@ -198,15 +301,42 @@ impl ChessBoard {
         // Parse out the castling rights from the 3rd FEN substring
         //
         let castling_rights = fen_string_split[2];
+        let mut castling_rook_columns = STANDARD_CASTLING_ROOK_COLUMNS;
         if castling_rights != "-" {
             for char in castling_rights.chars() {
-                match char {
-                    'K' => state.white_castle_kingside = true,
-                    'Q' => state.white_castle_queenside = true,
-                    'k' => state.black_castle_kingside = true,
-                    'q' => state.black_castle_queenside = true,
+                let side = match char.is_ascii_uppercase() {
+                    true => Side::White,
+                    false => Side::Black
+                };
+                let (kingside, rook_col) = match (variant, char.to_ascii_lowercase()) {
+                    (Variant::Standard, 'k') => (true, 7),
+                    (Variant::Standard, 'q') => (false, 0),
+                    // in Chess960, the castling rook is found relative to the king
+                    (Variant::Chess960, c) if ('a'..='h').contains(&c) || c == 'k' || c == 'q' => {
+                        let row = match side {
+                            Side::White => 0,
+                            Side::Black => 7
+                        };
+                        let king_col = (0..8).find(|col| squares[*col][row].is_some_and(|p| p.side == side && p.piece_type == PieceType::King))
+                            .ok_or_else(|| ChessError::InvalidState(format!("FEN string castling rights '{}' need a {:?} King on its first row: {}", char, side, fen_string)))?;
+                        let is_rook = |col: &usize| squares[*col][row].is_some_and(|p| p.side == side && p.piece_type == PieceType::Rook);
+                        let rook_col = match c {
+                            // K and Q mean the outermost rook on that side of the king
+                            'k' => (king_col + 1..8).rev().find(is_rook),
+                            'q' => (0..king_col).find(is_rook),
+                            _ => Some(c as usize - 'a' as usize).filter(is_rook),
+                        }.ok_or_else(|| ChessError::InvalidState(format!("FEN string castling rights '{}' don't have a matching {:?} Rook: {}", char, side, fen_string)))?;
+                        (rook_col > king_col, rook_col)
+                    },
                     _ => return Err(ChessError::InvalidState(format!("FEN string castling rights has invalid character '{}': {}", char, fen_string)))
+                };
+                match (side, kingside) {
+                    (Side::White, true) => state.white_castle_kingside = true,
+                    (Side::White, false) => state.white_castle_queenside = true,
+                    (Side::Black, true) => state.black_castle_kingside = true,
+                    (Side::Black, false) => state.black_castle_queenside = true,
                 }
+                castling_rook_columns[bitboard::side_index(side)][kingside as usize] = rook_col;
             }
         }
 
@@ -223,21 +353,15 @@ impl ChessBoard {
         }
 
         //
-        // Parse out halfmove and fullmove clock numbers from the 5th and 5th FEN substrings
-        // Even though these aren't used, we want to validate that FEN strings are valid
+        // Parse out halfmove and fullmove clock numbers from the 5th and 6th FEN substrings
         //
-        let _halfmove_clock = fen_string_split[4].parse::<usize>().map_err(|e| ChessError::InvalidState(format!("FEN string halfmove clock cannot be parsed as a number '{}': {}", fen_string_split[4], e.to_string())))?;
-        let _fullmove_clock = fen_string_split[5].parse::<usize>().map_err(|e| ChessError::InvalidState(format!("FEN string fullmove clock cannot be parsed as a number '{}': {}", fen_string_split[5], e.to_string())))?;
+        state.halfmove_clock = fen_string_split[4].parse::<usize>().map_err(|e| ChessError::InvalidState(format!("FEN string halfmove clock cannot be parsed as a number '{}': {}", fen_string_split[4], e)))?;
+        state.fullmove_number = fen_string_split[5].parse::<usize>().map_err(|e| ChessError::InvalidState(format!("FEN string fullmove clock cannot be parsed as a number '{}': {}", fen_string_split[5], e)))?;
 
-        Ok(ChessBoard {
-            squares,
-            state,
-            board_state_counts: HashMap::new(),
-            move_list: Vec::new()
-        })
+        Ok(Self::new_with_state(squares, state, variant, castling_rook_columns))
     }
 
-    /// Output a Forsyth-Edwards string of the current board state. Always uses 0 for the halfmove and fullmove clock.
+    /// Output a Forsyth-Edwards string of the current board state.
     pub fn to_forsyth_edwards(self: &Self) -> String {
         // figure out where all the pieces are
         let mut piece_placement = String::new();
@@ -272,17 +396,40 @@ impl ChessBoard {
 
         // determine what, if any, castling ability players have (ignoring temp restrictions)
         let mut castling_ability = String::new();
-        if self.state.white_castle_kingside {
-            castling_ability.push('K');
-        }
-        if self.state.white_castle_queenside {
-            castling_ability.push('Q');
-        }
-        if self.state.black_castle_kingside {
-            castling_ability.push('k');
-        }
-        if self.state.black_castle_queenside {
-            castling_ability.push('q');
+        let castle_rights = [
+            (self.state.white_castle_kingside, Side::White, true),
+            (self.state.white_castle_queenside, Side::White, false),
+            (self.state.black_castle_kingside, Side::Black, true),
+            (self.state.black_castle_queenside, Side::Black, false),
+        ];
+        for (has_right, side, kingside) in castle_rights {
+            if !has_right {
+                continue;
+            }
+            let mut c = match kingside {
+                true => 'K',
+                false => 'Q'
+            };
+            // Chess960 uses X-FEN, where the rook's file is given instead if there's another rook further out on the same side of the king
+            if self.variant == Variant::Chess960 {
+                let row = match side {
+                    Side::White => 0,
+                    Side::Black => 7
+                };
+                let rook_col = self.get_castling_rook_column(side, kingside);
+                let outer_cols = match kingside {
+                    true => rook_col + 1..8,
+                    false => 0..rook_col
+                };
+                let rooks = self.get_piece_bitboard(side, PieceType::Rook);
+                if outer_cols.into_iter().any(|col| rooks & bitboard::square_mask((col, row)) != bitboard::EMPTY) {
+                    c = (b'A' + rook_col as u8) as char;
+                }
+            }
+            castling_ability.push(match side {
+                Side::White => c,
+                Side::Black => c.to_ascii_lowercase()
+            });
         }
         if castling_ability.is_empty() {
             castling_ability = "-".to_string();
@@ -298,25 +445,22 @@ impl ChessBoard {
             None => "-".to_string(),
         };
 
-        let halfmove_clock = 0;  // TODO do I even need these?
-        let fullmove_click = 0;
-
-        format!("{} {} {} {} {} {}", piece_placement, active_side, castling_ability, en_passant_sqr, halfmove_clock, fullmove_click)
+        format!("{} {} {} {} {} {}", piece_placement, active_side, castling_ability, en_passant_sqr, self.state.halfmove_clock, self.state.fullmove_number)
     }
 
     pub fn get_total_materials(self: &Self, side: Side) -> usize {
-        self.squares.iter()
-            .map(
-                |row|
-                row.iter()
-                    .filter(|square| square.is_some())
-                    .filter(|square| square.unwrap().side == side)
-                    .map(|square| square.unwrap().get_material())
-                    .sum::<usize>()
-            )
+        self.get_all_pieces(side)
+            .iter()
+            .map(|piece| piece.get_material())
             .sum::<usize>()
     }
 
+    /// Every square of the board indexed by column and then row, where 0,0 = a1 and 7,7 = h8.
+    /// The squares are read-only since the bitboards have to be kept in sync with them, so use `make_move` or `ChessBoardBuilder` to change them.
+    pub fn get_squares(self: &Self) -> &[[Option<ChessPiece>; 8]; 8] {
+        &self.squares
+    }
+
     pub fn get_square_by_index(self: &Self, column: usize, row: usize) -> Option<ChessPiece> {
         // TODO change to result in case given indexes are known to be out of range? or just deal w/ potential run-time error
         self.squares[column][row]
@@ -331,43 +475,191 @@ impl ChessBoard {
         Ok(self.get_square_by_index(column_index, row_index))
     }
 
-    pub fn perform_move(self: &mut Self, chess_move: &ChessMove) -> Result<(), ()> {
+    /// Get the bitboard of every square holding a piece of the given side and type
+    pub fn get_piece_bitboard(self: &Self, side: Side, piece_type: PieceType) -> Bitboard {
+        self.pieces[bitboard::side_index(side)][bitboard::piece_index(piece_type)]
+    }
+
+    /// Get the bitboard of every square holding a piece of the given side
+    pub fn get_side_occupancy(self: &Self, side: Side) -> Bitboard {
+        self.occupancy[bitboard::side_index(side)]
+    }
+
+    /// Get the bitboard of every occupied square
+    pub fn get_occupancy(self: &Self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    /// Place (or remove, if None) a piece on a square, keeping the squares, bitboards, and Zobrist key in sync.
+    /// Every change to the board's pieces must go through here.
+    fn set_square(self: &mut Self, position: (usize, usize), square: Option<ChessPiece>) {
+        let mask = bitboard::square_mask(position);
+        if let Some(old_piece) = self.squares[position.0][position.1] {
+            let side = bitboard::side_index(old_piece.side);
+            self.pieces[side][bitboard::piece_index(old_piece.piece_type)] &= !mask;
+            self.occupancy[side] &= !mask;
+            self.zobrist_key ^= zobrist::piece_key(old_piece.side, old_piece.piece_type, position);
+        }
+        if let Some(mut new_piece) = square {
+            new_piece.position = position;
+            let side = bitboard::side_index(new_piece.side);
+            self.pieces[side][bitboard::piece_index(new_piece.piece_type)] |= mask;
+            self.occupancy[side] |= mask;
+            self.zobrist_key ^= zobrist::piece_key(new_piece.side, new_piece.piece_type, position);
+            self.squares[position.0][position.1] = Some(new_piece);
+        } else {
+            self.squares[position.0][position.1] = None;
+        }
+    }
+
+    /// The part of the Zobrist key which comes from the board state flags rather than the pieces.
+    fn get_state_key(self: &Self) -> u64 {
+        let mut key = 0;
+        if self.state.current_turn == Side::Black {
+            key ^= zobrist::black_to_move_key();
+        }
+        let castling_rights = [
+            self.state.white_castle_kingside,
+            self.state.white_castle_queenside,
+            self.state.black_castle_kingside,
+            self.state.black_castle_queenside,
+        ];
+        for (index, _) in castling_rights.iter().enumerate().filter(|(_, has_right)| **has_right) {
+            key ^= zobrist::castling_key(index);
+        }
+        // Positions only differ by en passant when the capture is actually possible, so only include it if a pawn is in place to capture
+        if let Some(column) = self.state.en_passant_column {
+            let capturing_row = match self.state.current_turn {
+                Side::White => 4,
+                Side::Black => 3,
+            };
+            let adjacent_columns = (column.saturating_sub(1)..=(column + 1).min(7)).filter(|c| *c != column);
+            let can_capture = adjacent_columns.into_iter().any(|c| {
+                self.get_piece_bitboard(self.state.current_turn, PieceType::Pawn) & bitboard::square_mask((c, capturing_row)) != bitboard::EMPTY
+            });
+            if can_capture {
+                key ^= zobrist::en_passant_key(column);
+            }
+        }
+        key
+    }
+
+    /// Get the Zobrist hash of the current position, covering piece placement, side to move, castling rights, and en passant.
+    /// It's kept up to date incrementally as moves are performed.
+    pub fn zobrist_key(self: &Self) -> u64 {
+        self.zobrist_key
+    }
+
+    /// Calculate the Zobrist hash of the current position from scratch. Should always be equal to `zobrist_key()`.
+    pub fn compute_zobrist_key(self: &Self) -> u64 {
+        let piece_keys = bitboard::positions(self.get_occupancy())
+            .filter_map(|position| self.get_square_by_position(position))
+            .fold(0, |key, piece| key ^ zobrist::piece_key(piece.side, piece.piece_type, piece.position));
+        piece_keys ^ self.get_state_key()
+    }
+
+    /// How many times the current position has occured in this game, including now.
+    pub fn get_repetition_count(self: &Self) -> usize {
+        self.board_state_counts.get(&self.zobrist_key).copied().unwrap_or_default()
+    }
+
+    /// Check that a move fits the position, i.e. there's a piece to move, it isn't landing on its own side's piece, and `captures` is the piece actually captured.
+    /// This doesn't check the move is legal, see `try_apply` for that.
+    pub fn check_move_consistency(self: &Self, chess_move: &ChessMove) -> Result<ChessPiece, ChessError> {
+        let from_name = index_pair_to_name(chess_move.from_square.0, chess_move.from_square.1)?;
+        let dest_name = index_pair_to_name(chess_move.destination.0, chess_move.destination.1)?;
+        let piece = match self.get_square_by_position(chess_move.from_square) {
+            Some(p) => p,
+            None => return Err(ChessError::InvalidMove(format!("There is no piece on {} to move", from_name)))
+        };
+        if chess_move.from_square == chess_move.destination {
+            return Err(ChessError::InvalidMove(format!("The {:?} on {} can't move to the square it's already on", piece.piece_type, from_name)));
+        }
+        let needs_type = match chess_move.move_type {
+            MoveType::Castle => Some(PieceType::King),
+            MoveType::EnPassant | MoveType::DoubleAdvance | MoveType::Promotion(_) => Some(PieceType::Pawn),
+            _ => None
+        };
+        if needs_type.is_some_and(|t| t != piece.piece_type) {
+            return Err(ChessError::InvalidMove(format!("A {:?} move can't be made by the {:?} on {}", chess_move.move_type, piece.piece_type, from_name)));
+        }
+        // the piece captured, if any, which for en passant is the pawn beside the moving one
+        let expected_capture = match chess_move.move_type {
+            // in Chess960 the destination of castling is the rook, which isn't captured
+            MoveType::Castle => None,
+            MoveType::EnPassant => {
+                let captured_position = (chess_move.destination.0, chess_move.from_square.1);
+                match self.get_square_by_position(captured_position) {
+                    Some(p) if p.side != piece.side && p.piece_type == PieceType::Pawn => Some(captured_position),
+                    _ => return Err(ChessError::InvalidMove(format!("There is no pawn to capture en passant with {}{}", from_name, dest_name)))
+                }
+            },
+            _ => match self.get_square_by_position(chess_move.destination) {
+                Some(p) if p.side == piece.side => return Err(ChessError::InvalidMove(format!("The {:?} on {} can't move onto its own side's {:?} on {}", piece.piece_type, from_name, p.piece_type, dest_name))),
+                Some(_) => Some(chess_move.destination),
+                None => None
+            }
+        };
+        if chess_move.captures != expected_capture {
+            let describe = |capture: Option<(usize, usize)>| match capture {
+                Some(position) => index_pair_to_name(position.0, position.1).unwrap_or_default(),
+                None => "nothing".to_string()
+            };
+            return Err(ChessError::InvalidMove(format!("Move {}{} says it captures {} but it would capture {}", from_name, dest_name, describe(chess_move.captures), describe(expected_capture))));
+        }
+        Ok(piece)
+    }
+
+    /// Check a move is legal for the side to move and then perform and record it, returning the info needed to take it back with `unmake_move`.
+    /// Use this for moves from outside the program, such as an opponent or a user, which may not be valid.
+    pub fn try_apply(self: &mut Self, chess_move: &ChessMove) -> Result<MoveUndo, ChessError> {
+        let piece = self.check_move_consistency(chess_move)?;
+        let from_name = index_pair_to_name(chess_move.from_square.0, chess_move.from_square.1)?;
+        let dest_name = index_pair_to_name(chess_move.destination.0, chess_move.destination.1)?;
+        if piece.side != self.state.current_turn {
+            return Err(ChessError::InvalidMove(format!("The {:?} on {} belongs to {:?} but it's {:?}'s turn", piece.piece_type, from_name, piece.side, self.state.current_turn)));
+        }
+        // the generated move also has to agree on the move type, otherwise e.g. a two square King move could skip the castling rules
+        let is_legal = piece.get_moves(self).iter().any(|m| m == chess_move && m.move_type == chess_move.move_type);
+        if !is_legal {
+            return Err(ChessError::InvalidMove(format!("The {:?} on {} can't legally move to {} in the current position", piece.piece_type, from_name, dest_name)));
+        }
+        self.make_move(chess_move)
+    }
+
+    /// Perform a move without recording it, after checking it's consistent with the position (see `check_move_consistency`).
+    pub fn perform_move(self: &mut Self, chess_move: &ChessMove) -> Result<(), ChessError> {
         let current_position = chess_move.from_square;
-        let mut piece = self.get_square_by_index(current_position.0, current_position.1).expect(format!("Tried to get a piece at position {:?} but piece didn't exist: {:?}", current_position, chess_move).as_str());
+        let mut piece = self.check_move_consistency(chess_move)?;
         let dest_col = chess_move.destination.0;
-        let dest_row = chess_move.destination.1;
+        // where the piece ends up, which is only different from the move destination when castling in Chess960
+        let mut destination = chess_move.destination;
+        let mut castling_rook = None;
+
+        // remove the old state from the key now, and add the new state once the move is done
+        self.zobrist_key ^= self.get_state_key();
 
         // handle special moves
         match chess_move.move_type {
             MoveType::EnPassant => {
-                let captured = match piece.side {
-                    Side::White => self.get_square_by_index(dest_col, dest_row - 1).expect(format!("Tried to perform en passant capture at position but piece didn't exist: {}\n{:#?}\n{:#?}", self.to_forsyth_edwards(), chess_move, self.state).as_str()),
-                    Side::Black => self.get_square_by_index(dest_col, dest_row + 1).expect(format!("Tried to perform en passant capture at position but piece didn't exist: {}\n{:#?}\n{:#?}", self.to_forsyth_edwards(), chess_move, self.state).as_str()),
-                };
-                self.squares[captured.position.0][captured.position.1] = None;
+                // the captured pawn is beside the moving one, which was checked above
+                self.set_square((dest_col, current_position.1), None);
                 self.state.en_passant_column = None;
             },
             MoveType::DoubleAdvance => {
                 self.state.en_passant_column = Some(dest_col);
             },
-            MoveType::Promotion => {
-                piece.piece_type = PieceType::Queen; // there's no reason why we would want a different piece type
+            MoveType::Promotion(promoted_type) => {
+                piece.piece_type = promoted_type;
                 self.state.en_passant_column = None;
             },
             MoveType::Castle => {
                 self.state.en_passant_column = None;
-                // the normal move of the king will be performed, but then we want to create a move for the rook and move it too
-                let (castle_from_col, castle_dest_col) = match dest_col == 2 {
-                    true => (0, 3),
-                    false => (7, 5)
-                };
-                let castle_move = ChessMove {
-                    from_square: (castle_from_col, dest_row),
-                    destination: (castle_dest_col, dest_row),
-                    move_type: MoveType::Standard,
-                    captures: None
-                };
-                self.perform_move(&castle_move)?;
+                // the rook is picked up now and put down after the king has moved, since in Chess960 the king might land where the rook was or the other way around
+                let (rook_from, rook_dest, king_dest) = self.get_castle_squares(piece.side, chess_move);
+                castling_rook = self.get_square_by_position(rook_from).map(|rook| (rook, rook_dest));
+                self.set_square(rook_from, None);
+                destination = king_dest;
             },
             _ => {
                 self.state.en_passant_column = None;
@@ -375,18 +667,11 @@ impl ChessBoard {
         }
         // handle board state flags when the rook moves off their starting square, removing the possibility for castling with that rook
         if piece.piece_type == PieceType::Rook {
-            match current_position {
-                // white queen's rook
-                (0, 0) => self.state.white_castle_queenside = false,
-                // white king's rook
-                (7, 0) => self.state.white_castle_kingside = false,
-                // black queen's rook
-                (0, 7) => self.state.black_castle_queenside = false,
-                // black king's rook
-                (7, 7) => self.state.black_castle_kingside = false,
-                // if it's any move other than off the starting square, no flags need to be changed
-                _ => ()
-            }
+            self.remove_castle_rights_for_rook(current_position);
+        }
+        // capturing a rook on its starting square also removes the possibility for castling with that rook
+        if let Some(captured_position) = chess_move.captures {
+            self.remove_castle_rights_for_rook(captured_position);
         }
         // if the king is what moved, unset the flags to disable castling
         if piece.piece_type == PieceType::King {
@@ -402,125 +687,268 @@ impl ChessBoard {
             }
         }
 
+        // the halfmove clock resets whenever a pawn moves or a piece is captured, and the fullmove number goes up once Black has moved
+        if piece.piece_type == PieceType::Pawn || chess_move.captures.is_some() {
+            self.state.halfmove_clock = 0;
+        } else {
+            self.state.halfmove_clock += 1;
+        }
+        if piece.side == Side::Black {
+            self.state.fullmove_number += 1;
+        }
+
         // move piece from current position to destination
-        piece.position = chess_move.destination;
-        self.squares[current_position.0][current_position.1] = None;
-        self.squares[dest_col][dest_row] = Some(piece);
+        self.set_square(current_position, None);
+        self.set_square(destination, Some(piece));
+        if let Some((rook, rook_dest)) = castling_rook {
+            self.set_square(rook_dest, Some(rook));
+        }
+
+        // after a move it's always the other side's turn
+        self.state.current_turn = !piece.side;
+        self.zobrist_key ^= self.get_state_key();
 
         Ok(())
     }
 
-    pub fn record_board_state(self: &mut Self) -> () {
-        let new_state_hash = self.get_board_state_hash();
-        let state_seen_count = self.board_state_counts.entry(new_state_hash).or_default();
-        *state_seen_count = *state_seen_count + 1;
+    pub fn record_board_state(self: &mut Self) {
+        let state_seen_count = self.board_state_counts.entry(self.zobrist_key).or_default();
+        *state_seen_count += 1;
     }
 
-    pub fn perform_move_and_record(self: &mut Self, chess_move: &ChessMove) -> Result<(), ()> {
-        self.state.current_turn = !self.get_square_by_position(chess_move.from_square).unwrap().side;
+    pub fn perform_move_and_record(self: &mut Self, chess_move: &ChessMove) -> Result<(), ChessError> {
         self.perform_move(chess_move)?;
         self.record_board_state();
         self.move_list.push(chess_move.clone());
         Ok(())
     }
 
+    /// Perform and record a move, returning the info needed to take it back again with `unmake_move`.
+    pub fn make_move(self: &mut Self, chess_move: &ChessMove) -> Result<MoveUndo, ChessError> {
+        let moved_piece = self.check_move_consistency(chess_move)?;
+        // en passant is the only move where the captured piece isn't on the destination square
+        let captured_piece = match chess_move.move_type {
+            MoveType::EnPassant => chess_move.captures.and_then(|position| self.get_square_by_position(position)),
+            // in Chess960 the destination of castling is the rook, which isn't captured
+            MoveType::Castle => None,
+            _ => self.get_square_by_position(chess_move.destination),
+        };
+        let undo = MoveUndo {
+            chess_move: chess_move.clone(),
+            moved_piece,
+            captured_piece,
+            previous_state: self.state,
+            previous_zobrist_key: self.zobrist_key
+        };
+        self.perform_move_and_record(chess_move)?;
+        Ok(undo)
+    }
+
+    /// Take back the last move performed with `make_move`, restoring pieces, state flags, move list, and repetition counts.
+    /// Undo records must be given back in the reverse order that the moves were made.
+    pub fn unmake_move(self: &mut Self, undo: MoveUndo) {
+        // forget this position was ever reached
+        if let Some(count) = self.board_state_counts.get_mut(&self.zobrist_key) {
+            *count -= 1;
+            if *count == 0 {
+                self.board_state_counts.remove(&self.zobrist_key);
+            }
+        }
+        self.move_list.pop();
+
+        let chess_move = undo.chess_move;
+        // put the king and rook back where they were before castling
+        if chess_move.move_type == MoveType::Castle {
+            let (rook_from, rook_dest, king_dest) = self.get_castle_squares(undo.moved_piece.side, &chess_move);
+            let rook = self.get_square_by_position(rook_dest);
+            self.set_square(king_dest, None);
+            self.set_square(rook_dest, None);
+            self.set_square(rook_from, rook);
+        } else {
+            self.set_square(chess_move.destination, None);
+        }
+        self.set_square(chess_move.from_square, Some(undo.moved_piece));
+        if let Some(captured) = undo.captured_piece {
+            self.set_square(captured.position, Some(captured));
+        }
+
+        self.state = undo.previous_state;
+        self.zobrist_key = undo.previous_zobrist_key;
+    }
+
+    /// Copy the position without the game history (move list and repetition counts), which is much cheaper than a full clone.
+    /// Useful for scratch boards where moves are tried out and then taken back.
+    pub fn clone_without_history(self: &Self) -> Self {
+        ChessBoard {
+            squares: self.squares,
+            pieces: self.pieces,
+            occupancy: self.occupancy,
+            state: self.state,
+            zobrist_key: self.zobrist_key,
+            board_state_counts: HashMap::new(),
+            move_list: Vec::new(),
+            starting_squares: self.squares,
+            starting_state: self.state,
+            variant: self.variant,
+            castling_rook_columns: self.castling_rook_columns
+        }
+    }
+
+    /// Get a new board set up how this one was before any of the moves in the move list were performed.
+    pub fn get_starting_board(self: &Self) -> ChessBoard {
+        Self::new_with_state(self.starting_squares, self.starting_state, self.variant, self.castling_rook_columns)
+    }
+
+    /// Get the column of the rook the side would castle with, which is always the a and h file in standard chess.
+    pub fn get_castling_rook_column(self: &Self, side: Side, kingside: bool) -> usize {
+        self.castling_rook_columns[bitboard::side_index(side)][kingside as usize]
+    }
+
+    /// For a castling move, get where the rook moves from and to, and where the King ends up.
+    /// Castling moves are to the left of the King for queenside and the right for kingside, since in Chess960 the destination is the rook.
+    pub(crate) fn get_castle_squares(self: &Self, side: Side, chess_move: &ChessMove) -> ((usize, usize), (usize, usize), (usize, usize)) {
+        let row = chess_move.from_square.1;
+        let kingside = chess_move.destination.0 > chess_move.from_square.0;
+        let (rook_dest_col, king_dest_col) = match kingside {
+            true => (5, 6),
+            false => (3, 2)
+        };
+        ((self.get_castling_rook_column(side, kingside), row), (rook_dest_col, row), (king_dest_col, row))
+    }
+
+    /// Remove the castling rights which use the rook starting on the given square, if there are any
+    fn remove_castle_rights_for_rook(self: &mut Self, position: (usize, usize)) {
+        let rights = [
+            (Side::White, false, &mut self.state.white_castle_queenside),
+            (Side::White, true, &mut self.state.white_castle_kingside),
+            (Side::Black, false, &mut self.state.black_castle_queenside),
+            (Side::Black, true, &mut self.state.black_castle_kingside),
+        ];
+        for (side, kingside, has_right) in rights {
+            let row = match side {
+                Side::White => 0,
+                Side::Black => 7
+            };
+            if position == (self.castling_rook_columns[bitboard::side_index(side)][kingside as usize], row) {
+                *has_right = false;
+            }
+        }
+    }
+
+    /// Get the bitboard of every square the side's pieces threaten, i.e. any square they could capture on.
+    pub fn get_threatened_bitboard(self: &Self, side: Side) -> Bitboard {
+        self.get_all_pieces(side)
+            .iter()
+            .fold(bitboard::EMPTY, |threatened, piece| threatened | piece.get_threat_bitboard(self))
+    }
+
     pub fn get_threatened(self: &Self, side: Side) -> Vec<(usize, usize)> {
         let mut threatened = Vec::new();
-        // for every column and row
-        for columns in &self.squares {
-            for square in columns {
-                // ignore if the square is empty or the piece is not the side of interest
-                if square.is_none() || square.unwrap().side != side {
-                    continue;
-                }
-                // generate a list of possible captures and then add to the list.
-                let mut piece_threats = square.unwrap().get_threats(&self);
-                threatened.append(&mut piece_threats);
-            }
+        for piece in self.get_all_pieces(side) {
+            // generate a list of possible captures and then add to the list.
+            let mut piece_threats = piece.get_threats(self);
+            threatened.append(&mut piece_threats);
         }
         threatened
     }
 
     pub fn get_threatened_map(self: &Self, side: Side) -> HashSet<(usize, usize)> {
-        let threatened_squares_vec = self.get_threatened(side);
-        let mut threatened = HashSet::new();
-        for square in threatened_squares_vec {
-            threatened.insert(square);
-        }
-        threatened
+        bitboard::positions(self.get_threatened_bitboard(side)).collect()
+    }
+
+    /// Get the bitboard of every piece of the given side that threatens the square.
+    pub fn get_attackers_bitboard(self: &Self, side: Side, square: (usize, usize)) -> Bitboard {
+        self.get_attackers_with_occupancy(side, square, self.get_occupancy())
+    }
+
+    /// Get the pieces of the side attacking the square if only the pieces in the occupancy were on the board.
+    /// Removing pieces from the occupancy reveals the x-ray attackers behind them.
+    pub(crate) fn get_attackers_with_occupancy(self: &Self, side: Side, square: (usize, usize), occupancy: Bitboard) -> Bitboard {
+        let rooks_and_queens = self.get_piece_bitboard(side, PieceType::Rook) | self.get_piece_bitboard(side, PieceType::Queen);
+        let bishops_and_queens = self.get_piece_bitboard(side, PieceType::Bishop) | self.get_piece_bitboard(side, PieceType::Queen);
+        // attacks are symmetric, so look outwards from the square using each piece's movement and see which pieces are hit
+        ((bitboard::pawn_attacks(!side, square) & self.get_piece_bitboard(side, PieceType::Pawn))
+            | (bitboard::knight_attacks(square) & self.get_piece_bitboard(side, PieceType::Knight))
+            | (bitboard::king_attacks(square) & self.get_piece_bitboard(side, PieceType::King))
+            | (bitboard::rook_attacks(square, occupancy) & rooks_and_queens)
+            | (bitboard::bishop_attacks(square, occupancy) & bishops_and_queens))
+            & occupancy
     }
 
     pub fn is_square_threatened(self: &Self, side: Side, square: (usize, usize)) -> bool {
-        let threatened = self.get_threatened_map(side);
-        threatened.contains(&square)
+        self.get_attackers_bitboard(side, square) != bitboard::EMPTY
     }
 
     pub fn get_square_threats(self: &Self, side: Side, square: (usize, usize)) -> Vec<ChessPiece> {
-        let mut threateners = Vec::new();
-
-        for col in self.squares {
-            for cell in col {
-                if cell.is_none() {
-                    continue;
-                }
-                let piece = cell.unwrap();
-                if piece.side != side {
-                    continue;
-                }
-                let piece_threats = piece.get_threats(&self);
-                if piece_threats.contains(&square) {
-                    threateners.push(piece)
-                }
-            }
-        }
-        threateners
+        bitboard::positions(self.get_attackers_bitboard(side, square))
+            .filter_map(|position| self.get_square_by_position(position))
+            .collect()
     }
 
     pub fn is_checked(self: &Self, side: Side) -> bool {
-        let king_piece = self.squares.iter()
-            .find_map(|row| {
-                row.iter()
-                    .find(
-                        |square|
-                        square.is_some() && (square.unwrap().piece_type == PieceType::King && square.unwrap().side == side)
-                    )
-                    .map(|s| s.clone()
-                )
-            })
-            .unwrap()
-            .unwrap();
-        self.is_square_threatened(!side, king_piece.position)
+        let king_position = bitboard::positions(self.get_piece_bitboard(side, PieceType::King))
+            .next()
+            .unwrap_or_else(|| panic!("There is no {:?} King on the board", side));
+        self.is_square_threatened(!side, king_position)
     }
 
     pub fn get_all_pieces(self: &Self, side: Side) -> Vec<ChessPiece> {
-        let mut pieces = Vec::new();
-        for columns in self.squares {
-            for square in columns {
-                // ignore if the square is empty or the piece is not the side of interest
-                if square.is_none() || square.unwrap().side != side {
-                    continue;
-                }
-                pieces.push(square.unwrap());
-            }
-        }
-        pieces
+        bitboard::positions(self.get_side_occupancy(side))
+            .filter_map(|position| self.get_square_by_position(position))
+            .collect()
     }
 
     pub fn get_all_moves(self: &Self, side: Side) -> Vec<ChessMove> {
+        self.get_filtered_moves(side, MoveFilter::All)
+    }
+
+    /// Get only the legal moves of the given kind, e.g. just the captures, which is quicker than generating every move and filtering them
+    pub fn get_filtered_moves(self: &Self, side: Side, filter: MoveFilter) -> Vec<ChessMove> {
+        if filter == MoveFilter::Checks {
+            return self.get_checking_moves(side);
+        }
         let mut all_moves = Vec::new();
         let all_pieces = self.get_all_pieces(side);
         for piece in all_pieces {
-            let mut moves = piece.get_moves(&self);
+            let mut moves = piece.get_filtered_pseudo_legal_moves(self, filter);
             all_moves.append(&mut moves);
         }
-        all_moves
+        // check all moves of every piece against a single scratch board
+        remove_self_checks(self, all_moves)
     }
 
-    pub fn get_board_state_hash(self: &Self) -> u64 {
-        let board_formatted = format!("{}", self);
-        let mut hasher = DefaultHasher::new();
-        board_formatted.hash(&mut hasher);
-        hasher.finish()
+    /// Get the legal moves which put the opponent in check. Only the moves which could possibly check are tried out on the board, which are
+    /// moves to squares attacking the King, moves by pieces which might be blocking a line to the King, and every pawn and King move.
+    fn get_checking_moves(self: &Self, side: Side) -> Vec<ChessMove> {
+        let king_position = match bitboard::positions(self.get_piece_bitboard(!side, PieceType::King)).next() {
+            Some(position) => position,
+            None => return Vec::new()
+        };
+        let occupancy = self.get_occupancy();
+        // any piece seen from the King could be blocking an attack on it, so moving it away might be a discovered check
+        let possible_blockers = bitboard::queen_attacks(king_position, occupancy);
+        let mut candidate_moves = Vec::new();
+        for piece in self.get_all_pieces(side) {
+            // promotions, en passant, and castling can all check in ways that are awkward to work out, so try all pawn and King moves
+            let targets = match piece.piece_type {
+                _ if possible_blockers & bitboard::square_mask(piece.position) != bitboard::EMPTY => !bitboard::EMPTY,
+                PieceType::Pawn | PieceType::King => !bitboard::EMPTY,
+                PieceType::Knight => bitboard::knight_attacks(king_position),
+                PieceType::Bishop => bitboard::bishop_attacks(king_position, occupancy),
+                PieceType::Rook => bitboard::rook_attacks(king_position, occupancy),
+                PieceType::Queen => bitboard::queen_attacks(king_position, occupancy)
+            };
+            candidate_moves.extend(piece.get_pseudo_legal_moves(self).into_iter().filter(|m| bitboard::square_mask(m.destination) & targets != bitboard::EMPTY));
+        }
+        let mut scratch_board = self.clone_without_history();
+        remove_self_checks(self, candidate_moves).into_iter()
+            .filter(|m| {
+                let undo = scratch_board.make_move(m).expect("Tried to check a generated move which couldn't be performed");
+                let gives_check = scratch_board.is_checked(!side);
+                scratch_board.unmake_move(undo);
+                gives_check
+            })
+            .collect()
     }
 
     /// Checks if there's a game ending state for the given board.
@@ -532,11 +960,11 @@ impl ChessBoard {
                 let white_is_checked = self.is_checked(Side::White);
                 let white_has_no_moves = self.get_all_moves(Side::White).is_empty();
                 if white_is_checked && white_has_no_moves {
-                    return Some(GameEnd::BlackVictory("Checkmate".to_string()));
+                    return Some(GameEnd::BlackVictory(GameEndReason::Checkmate));
                 }
                 if white_has_no_moves {
-                    // If there are no valid moves which White can make, that means the game is in a draw
-                    return Some(GameEnd::Draw("White has no valid moves".to_string()));
+                    // If there are no valid moves which White can make but they aren't in check, that's a stalemate
+                    return Some(GameEnd::Draw(GameEndReason::Stalemate));
                 }
             },
             Side::Black => {
@@ -544,127 +972,51 @@ impl ChessBoard {
                 let black_has_no_moves = self.get_all_moves(Side::Black).is_empty();
                 if black_is_checked && black_has_no_moves {
                     // White achieved Checkmate if Black remains in Check and has no valid moves remaining to escape
-                    return Some(GameEnd::WhiteVictory("Checkmate".to_string()));
+                    return Some(GameEnd::WhiteVictory(GameEndReason::Checkmate));
                 } else if black_has_no_moves {
-                    // If there are no valid moves which White can make, that means the game is in a draw
-                    return Some(GameEnd::Draw("Black has no valid moves".to_string()));
+                    // If there are no valid moves which Black can make but they aren't in check, that's a stalemate
+                    return Some(GameEnd::Draw(GameEndReason::Stalemate));
                 }
             }
         }
 
-        // otherwise check for stalemate / insufficient materials
-
-        // Check for insufficient material game ending. This occurs when one side only has a king, or both sides have their king plus a minot piece (bishop or knight)
-        let white_pieces = self.get_all_pieces(Side::White);
-        let black_pieces = self.get_all_pieces(Side::Black);
+        // If no ending state has been identified, the game goes on
+        self.get_draw_reason().map(GameEnd::Draw)
+    }
 
-        // Game is a draw if both sides are left with only the king
-        if white_pieces.len() == 1 && black_pieces.len() == 1 {
-            return Some(GameEnd::Draw("Stalemate".to_string()));
+    /// Checks for the draws which don't depend on the moves the side to move has, i.e. a dead position, the move clock, or repetition.
+    pub fn get_draw_reason(self: &Self) -> Option<GameEndReason> {
+        // check if nobody can ever checkmate, because of insufficient material or a locked position
+        if let Some(reason) = self.get_dead_position() {
+            return Some(reason);
         }
-        // Game ends in a draw if White only has their king, ...
-        else if white_pieces.len() == 1 {
-            // and a Knight/Bishop
-            if black_pieces.len() == 2 && black_pieces.iter().find(|p| p.piece_type != PieceType::King).unwrap().get_material() == 3 {
-                return Some(GameEnd::Draw("Insufficient material".to_string()));
-            }
-            // or just two Knights
-            else if black_pieces.len() == 3 && black_pieces.iter().filter(|p| p.piece_type != PieceType::King).filter(|p| p.piece_type == PieceType::Knight).nth(1).is_some() {
-                return Some(GameEnd::Draw("Insufficient material".to_string()));
-            }
-        }
-        // Game ends in a draw if Black only has their King, ...
-        else if black_pieces.len() == 1 {
-            // and a Knight/Bishop
-            if white_pieces.len() == 2 && white_pieces.iter().find(|p| p.piece_type != PieceType::King).unwrap().get_material() == 3 {
-                return Some(GameEnd::Draw("Insufficient material".to_string()));
-            }
-            // or has just 2 Knights
-            else if white_pieces.len() == 3 && white_pieces.iter().filter(|p| p.piece_type != PieceType::King).filter(|p| p.piece_type == PieceType::Knight).nth(1).is_some() {
-                return Some(GameEnd::Draw("Insufficient material".to_string()));
-            }
+
+        // check for draws by the move clock. After 75 moves by each side without a capture or pawn move the game is automatically drawn,
+        // and after 50 moves it can be claimed -- which the bot always will.
+        if self.state.halfmove_clock >= 150 {
+            return Some(GameEndReason::SeventyFiveMoveRule);
         }
-        // Game ends in a Draw if both sides have their Kings and a Knight/Bishop piece each
-        else if white_pieces.len() == 2 && black_pieces.len() == 2 && white_pieces.iter().find(|p| p.piece_type != PieceType::King).unwrap().get_material() == 3 && black_pieces.iter().find(|p| p.piece_type != PieceType::King).unwrap().get_material() == 3 {
-            return Some(GameEnd::Draw("Insufficient material".to_string()));
+        if self.state.halfmove_clock >= 100 {
+            return Some(GameEndReason::FiftyMoveRule);
         }
 
-        // check for draw by repition. If any board state hash has occured 3 or more times, it's a draw.
-        if self.board_state_counts.values().find(|v| **v == 3).is_some() {
-            return Some(GameEnd::Draw("Draw by repetition".to_string()));
+        // check for draw by repetition. If the current position has occured 5 times it's automatically a draw, and after 3 times it can be claimed.
+        // Positions are compared by Zobrist key, so they must also match in side to move, castling rights, and en passant as per FIDE rules.
+        let repetitions = self.get_repetition_count();
+        if repetitions >= 5 {
+            return Some(GameEndReason::FivefoldRepetition);
+        }
+        if repetitions >= 3 {
+            return Some(GameEndReason::ThreefoldRepetition);
         }
-
-        // If no ending state has been identified, the game goes on
         None
     }
 }
 
 
-// TODO make a pretty print function that can support an overlay to make things like movement maps or threat maps
+/// Print the board with the squares each side threatens highlighted, see `BoardRenderer` for other ways to draw it
 impl Display for ChessBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let white_threat_map = self.get_threatened_map(Side::White);
-        let black_threat_map = self.get_threatened_map(Side::Black);
-        // print rows in reverse since the numbers increase from bottom to top
-        for row_indx in (0..8).rev() {
-            write!(f, "{} ", format!("{}", row_indx+1).black())?;
-            for col_indx in 0..8 {
-                let char = match &self.squares[col_indx][row_indx] {
-                    Some(piece) => {
-                        match piece.side {
-                            Side::White => match piece.piece_type {
-                                PieceType::Pawn => "♙ ",
-                                PieceType::Rook => "♖ ",
-                                PieceType::Knight => "♘ ",
-                                PieceType::Bishop => "♗ ",
-                                PieceType::Queen => "♕ ",
-                                PieceType::King => "♔ ",
-                            }.white(),
-                            Side::Black => match piece.piece_type {
-                                PieceType::Pawn => "♟︎ ",
-                                PieceType::Rook => "♜ ",
-                                PieceType::Knight => "♞ ",
-                                PieceType::Bishop => "♝ ",
-                                PieceType::Queen => "♛ ",
-                                PieceType::King => "♚ ",
-                            }.blue(),
-                            // to swap print style to non-unicode, comment out above and replace with below
-                            // Side::White => match piece.piece_type {
-                            //     PieceType::Pawn => "P ",
-                            //     PieceType::Rook => "R ",
-                            //     PieceType::Knight => "N ",
-                            //     PieceType::Bishop => "B ",
-                            //     PieceType::Queen => "Q ",
-                            //     PieceType::King => "K ",
-                            // }.white(),
-                            // Side::Black => match piece.piece_type {
-                            //     PieceType::Pawn => "p ",
-                            //     PieceType::Rook => "r ",
-                            //     PieceType::Knight => "n ",
-                            //     PieceType::Bishop => "b ",
-                            //     PieceType::Queen => "q ",
-                            //     PieceType::King => "k ",
-                            // }.blue(),
-                        }
-                    },
-                    None => "╶╴".truecolor(128, 128, 128)
-                };
-                let white_threat = white_threat_map.contains(&(col_indx, row_indx));
-                let black_threat = black_threat_map.contains(&(col_indx, row_indx));
-                if white_threat && black_threat {
-                    write!(f, "{}", char.on_green())?;
-                } else if white_threat {
-                    write!(f, "{}", char.on_white())?;
-                } else if black_threat {
-                    write!(f, "{}", char.on_blue())?;
-                } else {
-                    write!(f, "{}", char)?;
-                }
-                // write!(f, "{}", char)?; // write w/ no background
-            }
-            write!(f, "\n")?;
-        };
-        write!(f, "  {}\n", "a b c d e f g h".black())?;
-        Ok(())
+        write!(f, "{}", BoardRenderer::new(self).threats(Side::White).threats(Side::Black))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,194 @@
+use super::{bitboard, board::{BoardStateFlags, ChessBoard, STANDARD_CASTLING_ROOK_COLUMNS}, name_to_index_pair, pieces::{ChessPiece, PieceType}, ChessError, Side, Variant};
+
+
+/// Build up a custom position piece by piece, e.g. for tests
+/// ```
+/// use chessbot_lib::gamelogic::{builder::ChessBoardBuilder, pieces::PieceType, Side};
+/// let board = ChessBoardBuilder::new()
+///     .place("e1", Side::White, PieceType::King)
+///     .place("h1", Side::White, PieceType::Rook)
+///     .place("e8", Side::Black, PieceType::King)
+///     .castling(Side::White, true, true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ChessBoardBuilder {
+    squares: [[Option<ChessPiece>; 8]; 8],
+    state: BoardStateFlags,
+    variant: Variant,
+    castling_rook_columns: [[usize; 2]; 2],
+    error: Option<ChessError>  // the first bad argument given, which is returned when building
+}
+
+impl Default for ChessBoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChessBoardBuilder {
+    /// Start with an empty board, White to move, and nobody able to castle
+    pub fn new() -> Self {
+        Self {
+            squares: Default::default(),
+            state: BoardStateFlags {
+                white_castle_queenside: false,
+                white_castle_kingside: false,
+                black_castle_queenside: false,
+                black_castle_kingside: false,
+                ..Default::default()
+            },
+            variant: Variant::Standard,
+            castling_rook_columns: STANDARD_CASTLING_ROOK_COLUMNS,
+            error: None
+        }
+    }
+
+    /// Start with the current position of an existing board
+    pub fn from_board(board: &ChessBoard) -> Self {
+        let mut builder = Self::new();
+        for (col, column) in builder.squares.iter_mut().enumerate() {
+            for (row, square) in column.iter_mut().enumerate() {
+                *square = board.get_square_by_index(col, row);
+            }
+        }
+        builder.state = board.state;
+        builder.variant = board.variant;
+        for side in [Side::White, Side::Black] {
+            for kingside in [false, true] {
+                builder.castling_rook_columns[bitboard::side_index(side)][kingside as usize] = board.get_castling_rook_column(side, kingside);
+            }
+        }
+        builder
+    }
+
+    /// Start with the pieces drawn in a diagram. Each row starts with its number and has a character per square,
+    /// upper case for White and lower case for Black like FEN, and `·` or `.` for an empty square. A line of column letters at the end is optional.
+    /// ```text
+    /// 8 ·k···b·r
+    /// 7 pp···ppp
+    /// 6 ··p····q
+    /// 5 ···PpP··
+    /// 4 QP··b···
+    /// 3 P····n·N
+    /// 2 ···P·PP·
+    /// 1 R·B·R··K
+    ///   abcdefgh
+    /// ```
+    pub fn from_diagram(diagram: &str) -> Result<Self, ChessError> {
+        let mut builder = Self::new();
+        let mut rows_seen = [false; 8];
+        for line in diagram.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            // the column letters are just a label
+            if line.chars().all(|c| ('a'..='h').contains(&c) || c.is_whitespace()) {
+                continue;
+            }
+            let (row_label, squares) = line.split_once(char::is_whitespace)
+                .ok_or_else(|| ChessError::InvalidArgument(format!("Diagram row must start with its number and a space: '{}'", line)))?;
+            let row = match row_label.parse::<usize>() {
+                Ok(n) if (1..=8).contains(&n) => n - 1,
+                _ => return Err(ChessError::InvalidArgument(format!("Diagram row number must be between 1 and 8: '{}'", line)))
+            };
+            if rows_seen[row] {
+                return Err(ChessError::InvalidArgument(format!("Diagram has row {} more than once", row + 1)));
+            }
+            rows_seen[row] = true;
+
+            let squares = squares.trim().chars().collect::<Vec<char>>();
+            if squares.len() != 8 {
+                return Err(ChessError::InvalidArgument(format!("Diagram row must have 8 squares, had {}: '{}'", squares.len(), line)));
+            }
+            for (col, c) in squares.into_iter().enumerate() {
+                if c == '·' || c == '.' {
+                    continue;
+                }
+                let side = match c.is_ascii_uppercase() {
+                    true => Side::White,
+                    false => Side::Black
+                };
+                let piece_type = PieceType::try_from(c)?;
+                builder.squares[col][row] = Some(ChessPiece { position: (col, row), side, piece_type });
+            }
+        }
+        if rows_seen.contains(&false) {
+            return Err(ChessError::InvalidArgument(format!("Diagram must have all 8 rows, only had {}", rows_seen.iter().filter(|r| **r).count())));
+        }
+        Ok(builder)
+    }
+
+    /// Put a piece on the square, replacing whatever was there
+    pub fn place(mut self: Self, square: &str, side: Side, piece_type: PieceType) -> Self {
+        if let Some(position) = self.parse_square(square) {
+            self.squares[position.0][position.1] = Some(ChessPiece { position, side, piece_type });
+        }
+        self
+    }
+
+    /// Remove whatever piece is on the square
+    pub fn remove(mut self: Self, square: &str) -> Self {
+        if let Some(position) = self.parse_square(square) {
+            self.squares[position.0][position.1] = None;
+        }
+        self
+    }
+
+    /// Set whether the side can still castle kingside or queenside
+    pub fn castling(mut self: Self, side: Side, kingside: bool, allowed: bool) -> Self {
+        match (side, kingside) {
+            (Side::White, true) => self.state.white_castle_kingside = allowed,
+            (Side::White, false) => self.state.white_castle_queenside = allowed,
+            (Side::Black, true) => self.state.black_castle_kingside = allowed,
+            (Side::Black, false) => self.state.black_castle_queenside = allowed,
+        }
+        self
+    }
+
+    /// Set the square a pawn skipped over by double advancing last move, which can be captured en passant. None clears it.
+    pub fn en_passant(mut self: Self, square: Option<&str>) -> Self {
+        self.state.en_passant_column = match square {
+            Some(name) => self.parse_square(name).map(|position| position.0),
+            None => None
+        };
+        self
+    }
+
+    pub fn turn(mut self: Self, side: Side) -> Self {
+        self.state.current_turn = side;
+        self
+    }
+
+    /// Set the half-moves since the last capture or pawn move, and the number of the current full move
+    pub fn clocks(mut self: Self, halfmove_clock: usize, fullmove_number: usize) -> Self {
+        self.state.halfmove_clock = halfmove_clock;
+        self.state.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Create the board. It doesn't validate the position, use `ChessBoard::validate` for that.
+    pub fn build(self: Self) -> Result<ChessBoard, ChessError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        Ok(ChessBoard::new_with_state(self.squares, self.state, self.variant, self.castling_rook_columns))
+    }
+
+    /// Get the position of a square name, or remember the error for when the board is built
+    fn parse_square(self: &mut Self, square: &str) -> Option<(usize, usize)> {
+        match name_to_index_pair(square.to_string()) {
+            Ok(position) => Some(position),
+            Err(e) => {
+                self.error.get_or_insert(e);
+                None
+            }
+        }
+    }
+}
+
+
+impl ChessBoard {
+    /// Create a board from a diagram of the pieces (see `ChessBoardBuilder::from_diagram`), with White to move and nobody able to castle
+    pub fn from_diagram(diagram: &str) -> Result<Self, ChessError> {
+        ChessBoardBuilder::from_diagram(diagram)?.build()
+    }
+}
@@ -0,0 +1,106 @@
+use super::{bitboard::{self, Bitboard, DARK_SQUARES, EMPTY, LIGHT_SQUARES}, board::ChessBoard, pieces::PieceType, GameEnd, GameEndReason, Side};
+
+
+impl ChessBoard {
+    /// Check if the side has enough material to ever checkmate its opponent by any series of legal moves, even if the opponent helps.
+    /// This decides whether running out of time loses or draws, as per the FIDE Laws of Chess article 6.9.
+    pub fn can_checkmate(self: &Self, side: Side) -> bool {
+        let all_of = |piece_type| self.get_piece_bitboard(Side::White, piece_type) | self.get_piece_bitboard(Side::Black, piece_type);
+        let own_pieces = self.get_side_occupancy(side);
+        let opponent_pieces = self.get_side_occupancy(!side);
+
+        // a pawn could promote, and a rook or queen can always force mate
+        if own_pieces & (all_of(PieceType::Pawn) | all_of(PieceType::Rook) | all_of(PieceType::Queen)) != EMPTY {
+            return true;
+        }
+        // a knight can mate with another piece's help, either its own or an opponent piece blocking the king in.
+        // A queen is never able to block without also covering the mating square, so it doesn't help.
+        if own_pieces & all_of(PieceType::Knight) != EMPTY {
+            let opponent_blockers = opponent_pieces & !all_of(PieceType::King) & !all_of(PieceType::Queen);
+            return own_pieces.count_ones() > 2 || opponent_blockers != EMPTY;
+        }
+        // bishops can only mate if there are bishops on both colours of square, or a knight or pawn which can block the other colour
+        if own_pieces & all_of(PieceType::Bishop) != EMPTY {
+            let bishops = all_of(PieceType::Bishop);
+            let same_coloured_bishops = bishops & DARK_SQUARES == EMPTY || bishops & LIGHT_SQUARES == EMPTY;
+            return !same_coloured_bishops || all_of(PieceType::Pawn) != EMPTY || all_of(PieceType::Knight) != EMPTY;
+        }
+        // only the king is left
+        false
+    }
+
+    /// Check if neither side has enough material to checkmate, which is an automatic draw
+    pub fn is_insufficient_material(self: &Self) -> bool {
+        !self.can_checkmate(Side::White) && !self.can_checkmate(Side::Black)
+    }
+
+    /// Check if the only pieces left are kings and pawns, and every pawn is permanently blocked by an opposing pawn
+    /// while neither king can ever reach a pawn it's able to capture. No piece can ever be captured or promoted, so nobody can win.
+    pub fn is_blocked_pawn_fortress(self: &Self) -> bool {
+        let pawns = [Side::White, Side::Black].map(|side| self.get_piece_bitboard(side, PieceType::Pawn));
+        let kings = [Side::White, Side::Black].map(|side| self.get_piece_bitboard(side, PieceType::King));
+        if pawns.iter().all(|p| *p == EMPTY) || self.get_occupancy() != pawns[0] | pawns[1] | kings[0] | kings[1] {
+            return false;
+        }
+        // a check or an en passant capture means something can still change
+        if self.state.en_passant_column.is_some() || self.is_checked(Side::White) || self.is_checked(Side::Black) {
+            return false;
+        }
+
+        for side in [Side::White, Side::Black] {
+            let own_pawns = pawns[bitboard::side_index(side)];
+            let opponent_pawns = pawns[bitboard::side_index(!side)];
+            for position in bitboard::positions(own_pawns) {
+                // a pawn can't be on the last row, so there's always a square in front of it
+                let forward_row = match side {
+                    Side::White => position.1 + 1,
+                    Side::Black => position.1 - 1,
+                };
+                let blocked = bitboard::square_mask((position.0, forward_row)) & opponent_pawns != EMPTY;
+                let can_capture = bitboard::pawn_attacks(side, position) & opponent_pawns != EMPTY;
+                if !blocked || can_capture {
+                    return false;
+                }
+            }
+
+            // flood fill all the squares the king could ever walk to, which can't change since the pawns are stuck
+            let guarded_by_opponent = bitboard::positions(opponent_pawns).fold(EMPTY, |guarded, p| guarded | bitboard::pawn_attacks(!side, p));
+            let walkable = !own_pawns & !guarded_by_opponent;
+            let mut reachable: Bitboard = kings[bitboard::side_index(side)];
+            loop {
+                let expanded = bitboard::positions(reachable).fold(reachable, |r, p| r | (bitboard::king_attacks(p) & walkable));
+                if expanded == reachable {
+                    break;
+                }
+                reachable = expanded;
+            }
+            if reachable & opponent_pawns != EMPTY {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Check if no series of legal moves could ever lead to checkmate, so the game is immediately drawn (FIDE Laws of Chess article 5.2.2).
+    /// Only insufficient material and blocked pawn fortresses are recognised, so this may say a dead position is still alive but never the reverse.
+    pub fn get_dead_position(self: &Self) -> Option<GameEndReason> {
+        if self.is_insufficient_material() {
+            Some(GameEndReason::InsufficientMaterial)
+        } else if self.is_blocked_pawn_fortress() {
+            Some(GameEndReason::DeadPosition)
+        } else {
+            None
+        }
+    }
+
+    /// Get the result when the given side runs out of time, which is a loss unless the opponent couldn't checkmate anyway
+    pub fn get_timeout_result(self: &Self, flagged_side: Side) -> GameEnd {
+        if !self.can_checkmate(!flagged_side) {
+            return GameEnd::Draw(GameEndReason::InsufficientMaterial);
+        }
+        match flagged_side {
+            Side::White => GameEnd::BlackVictory(GameEndReason::Timeout),
+            Side::Black => GameEnd::WhiteVictory(GameEndReason::Timeout),
+        }
+    }
+}
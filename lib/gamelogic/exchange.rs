@@ -0,0 +1,74 @@
+use super::{bitboard::{self, Bitboard, EMPTY}, board::ChessBoard, pieces::PieceType, ChessMove, MoveType, Side};
+
+/// Pieces join an exchange cheapest first
+const EXCHANGE_ORDER: [PieceType; 6] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King];
+
+
+impl ChessBoard {
+    /// Get how much material the side making the move wins (or loses, if negative) once both sides have finished capturing on the destination square.
+    /// Each side always captures with its cheapest piece and stops when capturing again would lose material. Attackers lined up behind other
+    /// pieces (x-rays) join in once the pieces in front of them have captured. Pins aren't considered, and the move must be legal.
+    /// https://www.chessprogramming.org/Static_Exchange_Evaluation
+    pub fn static_exchange_eval(self: &Self, chess_move: &ChessMove) -> i64 {
+        if chess_move.move_type == MoveType::Castle {
+            return 0;
+        }
+        let piece = match self.get_square_by_position(chess_move.from_square) {
+            Some(p) => p,
+            None => return 0
+        };
+        let mut occupancy = self.get_occupancy() & !bitboard::square_mask(chess_move.from_square);
+        let mut gain = 0;
+        if let Some(captured_position) = chess_move.captures {
+            gain += self.get_square_by_position(captured_position).map_or(0, |p| p.get_material() as i64);
+            // en passant captures a pawn which isn't on the destination square
+            occupancy &= !bitboard::square_mask(captured_position);
+        }
+        let mut moved_type = piece.piece_type;
+        if let Some(promoted_type) = chess_move.promotion_piece() {
+            gain += promoted_type.get_material() as i64 - PieceType::Pawn.get_material() as i64;
+            moved_type = promoted_type;
+        }
+        gain - self.get_exchange_gain(chess_move.destination, moved_type, !piece.side, occupancy)
+    }
+
+    /// Get how much material the owner of the piece on the square would lose if their opponent started capturing it, which is 0 if it's safe
+    pub fn get_exchange_loss(self: &Self, position: (usize, usize)) -> i64 {
+        match self.get_square_by_position(position) {
+            Some(piece) => self.get_exchange_gain(position, piece.piece_type, !piece.side, self.get_occupancy()),
+            None => 0
+        }
+    }
+
+    /// Check if the opponent would win material by capturing the piece on the square
+    pub fn is_hanging(self: &Self, position: (usize, usize)) -> bool {
+        self.get_exchange_loss(position) > 0
+    }
+
+    /// Get the material the side can win by starting a sequence of captures on the target square, if only the pieces in the occupancy are on the board
+    fn get_exchange_gain(self: &Self, target: (usize, usize), mut target_type: PieceType, mut side: Side, mut occupancy: Bitboard) -> i64 {
+        // the value of the piece taken by each capture, alternating between the sides
+        let mut captured_values = Vec::new();
+        loop {
+            let attackers = self.get_attackers_with_occupancy(side, target, occupancy);
+            let cheapest_attacker = EXCHANGE_ORDER.iter()
+                .find_map(|piece_type| bitboard::positions(attackers & self.get_piece_bitboard(side, *piece_type)).next().map(|p| (p, *piece_type)));
+            let (attacker_position, attacker_type) = match cheapest_attacker {
+                Some(a) => a,
+                None => break
+            };
+            // removing the attacker reveals anything lined up behind it
+            let remaining_occupancy = occupancy & !bitboard::square_mask(attacker_position);
+            // the King can't capture onto a square the opponent still attacks
+            if attacker_type == PieceType::King && self.get_attackers_with_occupancy(!side, target, remaining_occupancy) != EMPTY {
+                break;
+            }
+            captured_values.push(target_type.get_material() as i64);
+            target_type = attacker_type;
+            occupancy = remaining_occupancy;
+            side = !side;
+        }
+        // either side can stop capturing, so work backwards from the last capture to see which are worth making
+        captured_values.into_iter().rev().fold(0, |later_gain, captured| (captured - later_gain).max(0))
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use super::{bitboard, board::ChessBoard, pieces::{ChessPiece, PieceType}, render::Highlight, ChessError, Side};
+
+use resvg::{tiny_skia, usvg};
+
+/// Everything is drawn in units of 45 per square, and then scaled to the size of the image
+const SQUARE_UNITS: f64 = 45.0;
+const LIGHT_SQUARE_COLOR: &str = "#f0d9b5";
+const DARK_SQUARE_COLOR: &str = "#b58863";
+const ARROW_COLOR: &str = "#15781b";
+
+lazy_static! {
+    // loading the system fonts is slow, so only do it once. They're only needed for the coordinate labels.
+    static ref FONTS: Arc<usvg::fontdb::Database> = {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+        Arc::new(fonts)
+    };
+}
+
+
+/// Draws a board as an SVG or PNG image, with optional highlighted squares and arrows, e.g. for reports.
+/// Overlays use the same highlights as `BoardRenderer`, and are drawn in the order they're added.
+/// ```
+/// use chessbot_lib::gamelogic::{board::ChessBoard, image::BoardImage};
+/// let mut board = ChessBoard::new();
+/// let the_move = board.parse_san("e4").unwrap();
+/// board.make_move(&the_move).unwrap();
+/// let svg = BoardImage::new(&board).last_move().arrow((6, 0), (5, 2)).to_svg();
+/// assert!(svg.starts_with("<svg"));
+/// ```
+pub struct BoardImage<'a> {
+    board: &'a ChessBoard,
+    orientation: Side,  // the side at the bottom of the board
+    coordinates: bool,
+    square_size: u32,  // in pixels
+    overlays: Vec<((usize, usize), Highlight)>,
+    arrows: Vec<((usize, usize), (usize, usize))>
+}
+
+impl<'a> BoardImage<'a> {
+    /// Start with White at the bottom, coordinates shown, 64 pixel squares, and nothing highlighted
+    pub fn new(board: &'a ChessBoard) -> Self {
+        Self {
+            board,
+            orientation: Side::White,
+            coordinates: true,
+            square_size: 64,
+            overlays: Vec::new(),
+            arrows: Vec::new()
+        }
+    }
+
+    /// Draw the board from the point of view of the side, so their pieces are at the bottom
+    pub fn orientation(mut self: Self, side: Side) -> Self {
+        self.orientation = side;
+        self
+    }
+
+    /// Show the column letters and row numbers along the edges of the board
+    pub fn coordinates(mut self: Self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// The width and height of each square in pixels
+    pub fn square_size(mut self: Self, pixels: u32) -> Self {
+        self.square_size = pixels;
+        self
+    }
+
+    /// Highlight each of the squares
+    pub fn overlay(mut self: Self, squares: impl IntoIterator<Item = (usize, usize)>, highlight: Highlight) -> Self {
+        self.overlays.extend(squares.into_iter().map(|s| (s, highlight)));
+        self
+    }
+
+    /// Highlight the from and destination squares of the last recorded move, if there is one
+    pub fn last_move(self: Self) -> Self {
+        match self.board.move_list.last() {
+            Some(m) => {
+                let squares = [m.from_square, m.destination];
+                self.overlay(squares, Highlight::LastMove)
+            },
+            None => self
+        }
+    }
+
+    /// Highlight the King of the side to move if it's in check
+    pub fn check(self: Self) -> Self {
+        let side = self.board.state.current_turn;
+        let king = bitboard::positions(self.board.get_piece_bitboard(side, PieceType::King)).next();
+        match king {
+            Some(position) if self.board.is_checked(side) => self.overlay([position], Highlight::Check),
+            _ => self
+        }
+    }
+
+    /// Draw an arrow from one square to another, e.g. to show a planned move
+    pub fn arrow(mut self: Self, from_square: (usize, usize), destination: (usize, usize)) -> Self {
+        self.arrows.push((from_square, destination));
+        self
+    }
+
+    /// Get the image as an SVG document
+    pub fn to_svg(self: &Self) -> String {
+        let board_units = SQUARE_UNITS * 8.0;
+        let pixels = self.square_size * 8;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            pixels, pixels, board_units, board_units
+        );
+
+        for col in 0..8 {
+            for row in 0..8 {
+                let (x, y) = self.square_origin((col, row));
+                let color = match (col + row).is_multiple_of(2) {
+                    true => DARK_SQUARE_COLOR,  // a1 is a dark square
+                    false => LIGHT_SQUARE_COLOR
+                };
+                svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", x, y, SQUARE_UNITS, SQUARE_UNITS, color));
+            }
+        }
+        for (position, highlight) in self.overlays.iter() {
+            svg.push_str(&self.highlight_svg(*position, *highlight));
+        }
+        if self.coordinates {
+            svg.push_str(&self.coordinates_svg());
+        }
+        for col in 0..8 {
+            for row in 0..8 {
+                if let Some(piece) = self.board.get_square_by_index(col, row) {
+                    let (x, y) = self.square_origin((col, row));
+                    svg.push_str(&format!("<g transform=\"translate({},{})\">{}</g>\n", x, y, piece_svg(&piece)));
+                }
+            }
+        }
+        for (from_square, destination) in self.arrows.iter() {
+            svg.push_str(&self.arrow_svg(*from_square, *destination));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Rasterize the image into a PNG file's bytes
+    pub fn to_png(self: &Self) -> Result<Vec<u8>, ChessError> {
+        let options = usvg::Options {
+            fontdb: FONTS.clone(),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_str(&self.to_svg(), &options)
+            .map_err(|e| ChessError::InvalidState(format!("Board SVG could not be parsed: {}", e)))?;
+        let pixels = self.square_size * 8;
+        let mut pixmap = tiny_skia::Pixmap::new(pixels, pixels)
+            .ok_or_else(|| ChessError::InvalidArgument(format!("Board image can't be {} pixels wide", pixels)))?;
+        resvg::render(&tree, tiny_skia::Transform::identity(), &mut pixmap.as_mut());
+        pixmap.encode_png().map_err(|e| ChessError::InvalidState(format!("Board image could not be encoded as a PNG: {}", e)))
+    }
+
+    /// Get the top left corner of the square in SVG units, which depends on which side is at the bottom
+    fn square_origin(self: &Self, position: (usize, usize)) -> (f64, f64) {
+        let (column, row) = match self.orientation {
+            Side::White => (position.0, 7 - position.1),
+            Side::Black => (7 - position.0, position.1),
+        };
+        (column as f64 * SQUARE_UNITS, row as f64 * SQUARE_UNITS)
+    }
+
+    fn square_center(self: &Self, position: (usize, usize)) -> (f64, f64) {
+        let (x, y) = self.square_origin(position);
+        (x + SQUARE_UNITS / 2.0, y + SQUARE_UNITS / 2.0)
+    }
+
+    fn highlight_svg(self: &Self, position: (usize, usize), highlight: Highlight) -> String {
+        let (x, y) = self.square_origin(position);
+        let (center_x, center_y) = self.square_center(position);
+        let square = |color: &str, opacity: f64| format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>\n", x, y, SQUARE_UNITS, SQUARE_UNITS, color, opacity
+        );
+        match highlight {
+            Highlight::Threat(Side::White) => square("#ffffff", 0.4),
+            Highlight::Threat(Side::Black) => square("#1e50c8", 0.35),
+            Highlight::LastMove => square("#9bc700", 0.45),
+            Highlight::Check => square("#ff0000", 0.6),
+            // possible moves are shown as a dot in the middle of the square, so the piece on it can still be seen
+            Highlight::Move => format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#14551e\" fill-opacity=\"0.5\"/>\n", center_x, center_y, SQUARE_UNITS * 0.15),
+        }
+    }
+
+    /// Label the columns along the bottom and the rows along the left, in the opposite colour to the square they're on
+    fn coordinates_svg(self: &Self) -> String {
+        let mut svg = String::new();
+        let label_color = |position: (usize, usize)| match (position.0 + position.1).is_multiple_of(2) {
+            true => LIGHT_SQUARE_COLOR,
+            false => DARK_SQUARE_COLOR
+        };
+        let (bottom_row, left_column) = match self.orientation {
+            Side::White => (0, 0),
+            Side::Black => (7, 7),
+        };
+        for col in 0..8 {
+            let (x, y) = self.square_origin((col, bottom_row));
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"Arial, Helvetica, DejaVu Sans, sans-serif\" font-size=\"9\" font-weight=\"bold\" text-anchor=\"end\" fill=\"{}\">{}</text>\n",
+                x + SQUARE_UNITS - 2.0, y + SQUARE_UNITS - 2.0, label_color((col, bottom_row)), (b'a' + col as u8) as char
+            ));
+        }
+        for row in 0..8 {
+            let (x, y) = self.square_origin((left_column, row));
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"Arial, Helvetica, DejaVu Sans, sans-serif\" font-size=\"9\" font-weight=\"bold\" fill=\"{}\">{}</text>\n",
+                x + 2.0, y + 10.0, label_color((left_column, row)), row + 1
+            ));
+        }
+        svg
+    }
+
+    /// An arrow is a shaft from the middle of one square to a point in the middle of the other
+    fn arrow_svg(self: &Self, from_square: (usize, usize), destination: (usize, usize)) -> String {
+        let (start_x, start_y) = self.square_center(from_square);
+        let (end_x, end_y) = self.square_center(destination);
+        let length = ((end_x - start_x).powi(2) + (end_y - start_y).powi(2)).sqrt();
+        if length == 0.0 {
+            return String::new();
+        }
+        // unit vectors along and across the arrow
+        let (along_x, along_y) = ((end_x - start_x) / length, (end_y - start_y) / length);
+        let (across_x, across_y) = (-along_y, along_x);
+        let shaft_width = SQUARE_UNITS * 0.18;
+        let head_width = SQUARE_UNITS * 0.45;
+        let head_length = SQUARE_UNITS * 0.45;
+        let (head_x, head_y) = (end_x - along_x * head_length, end_y - along_y * head_length);
+
+        let points = [
+            (start_x + across_x * shaft_width / 2.0, start_y + across_y * shaft_width / 2.0),
+            (head_x + across_x * shaft_width / 2.0, head_y + across_y * shaft_width / 2.0),
+            (head_x + across_x * head_width / 2.0, head_y + across_y * head_width / 2.0),
+            (end_x, end_y),
+            (head_x - across_x * head_width / 2.0, head_y - across_y * head_width / 2.0),
+            (head_x - across_x * shaft_width / 2.0, head_y - across_y * shaft_width / 2.0),
+            (start_x - across_x * shaft_width / 2.0, start_y - across_y * shaft_width / 2.0),
+        ];
+        let points = points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect::<Vec<String>>().join(" ");
+        format!("<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"0.8\"/>\n", points, ARROW_COLOR)
+    }
+}
+
+
+/// Draw a piece in a 45 by 45 square. White pieces are white with a black outline, and Black pieces are the reverse.
+fn piece_svg(piece: &ChessPiece) -> String {
+    let (fill, detail) = match piece.side {
+        Side::White => ("#ffffff", "#000000"),
+        Side::Black => ("#333333", "#ffffff"),
+    };
+    let base = "M 10,39 L 35,39 L 35,35 L 10,35 Z";
+    // the outline of the piece, plus any lines drawn over it in the detail colour
+    let (outline, details) = match piece.piece_type {
+        PieceType::Pawn => (
+            "M 16,35 C 16,28 19,24 22.5,24 C 26,24 29,28 29,35 Z M 28.5,16 A 6,6 0 1,1 16.5,16 A 6,6 0 1,1 28.5,16 Z",
+            ""
+        ),
+        PieceType::Rook => (
+            "M 14,35 L 31,35 L 29,16 L 16,16 Z M 12,16 L 33,16 L 33,9 L 29,9 L 29,12 L 25,12 L 25,9 L 20,9 L 20,12 L 16,12 L 16,9 L 12,9 Z",
+            "M 16,20 L 29,20"
+        ),
+        PieceType::Knight => (
+            "M 14,35 L 31,35 C 31,26 30,17 24,11 L 22,7 L 20,11 C 16,13 12,19 10,24 L 12,27 C 15,25 17,24 20,23 C 18,27 15,30 14,35 Z",
+            "M 17,16 L 18,17"
+        ),
+        PieceType::Bishop => (
+            "M 15,35 C 13,27 17,19 22.5,12 C 28,19 32,27 30,35 Z M 25,9 A 2.5,2.5 0 1,1 20,9 A 2.5,2.5 0 1,1 25,9 Z",
+            "M 22.5,18 L 22.5,26 M 18.5,22 L 26.5,22"
+        ),
+        PieceType::Queen => (
+            "M 12,35 L 33,35 L 36,14 L 29,26 L 29.5,11 L 24.5,25 L 22.5,10 L 20.5,25 L 15.5,11 L 16,26 L 9,14 Z",
+            "M 13,31 L 32,31"
+        ),
+        PieceType::King => (
+            "M 13,35 L 32,35 L 34,22 C 30,18 26,19 22.5,23 C 19,19 15,18 11,22 Z M 22.5,6 L 22.5,21 M 18.5,10 L 26.5,10",
+            "M 14,31 L 31,31"
+        ),
+    };
+    let mut svg = format!(
+        "<path d=\"{} {}\" fill=\"{}\" stroke=\"#000000\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>",
+        base, outline, fill
+    );
+    if !details.is_empty() {
+        svg.push_str(&format!("<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" stroke-linecap=\"round\"/>", details, detail));
+    }
+    svg
+}
@@ -1,19 +1,38 @@
 use core::fmt;
 use std::error::Error;
 
+use pieces::PieceType;
+
+pub mod bitboard;
 pub mod board;
+pub mod builder;
+pub mod dead_position;
+pub mod exchange;
+pub mod image;
+pub mod pawns;
+pub mod perft;
+pub mod pgn;
 pub mod pieces;
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub mod render;
+pub mod san;
+pub mod tactics;
+pub mod uci;
+pub mod validation;
+pub mod zobrist;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Side {
+    #[default]
     White,
     Black
 }
 
-impl Default for Side {
-    fn default() -> Self {
-        Side::White
-    }
+/// The rules the game is played with. Chess960 (Fischer Random) shuffles the back row pieces, which mostly changes how castling works.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    Chess960
 }
 
 /// Different types of Errors related to chess logic specifically. All types wrap String containing a more detailed error message.
@@ -39,7 +58,17 @@ pub enum MoveType {
     DoubleAdvance,
     EnPassant,
     Castle,
-    Promotion,
+    Promotion(PieceType),  // the type of piece the pawn becomes
+}
+
+/// Which kinds of moves to generate, so a search can ask for e.g. only the captures without generating every move first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveFilter {
+    All,
+    Captures,  // including en passant and promotions which capture
+    CapturesAndPromotions,
+    Checks,  // any move which puts the opponent in check
+    Quiet,  // everything which isn't a capture or a promotion
 }
 
 /// Struct to hold info about chess moves and associated data we can use for filtering and move quality ranking
@@ -51,19 +80,87 @@ pub struct ChessMove {
     pub captures: Option<(usize, usize)>
 }
 
+impl ChessMove {
+    /// The type of piece a pawn is promoted to, if the move is a promotion
+    pub fn promotion_piece(self: &Self) -> Option<PieceType> {
+        match self.move_type {
+            MoveType::Promotion(piece_type) => Some(piece_type),
+            _ => None
+        }
+    }
+}
+
 impl PartialEq for ChessMove {
     fn eq(&self, other: &Self) -> bool {
-        self.from_square == other.from_square && self.destination == other.destination
+        self.from_square == other.from_square && self.destination == other.destination && self.promotion_piece() == other.promotion_piece()
     }
 }
 
 impl Eq for ChessMove {}
 
-#[derive(Debug)]
+/// The result of a game, and why it ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameEnd {
-    WhiteVictory(String),
-    BlackVictory(String),
-    Draw(String),
+    WhiteVictory(GameEndReason),
+    BlackVictory(GameEndReason),
+    Draw(GameEndReason),
+}
+
+impl GameEnd {
+    pub fn reason(self: &Self) -> GameEndReason {
+        match self {
+            GameEnd::WhiteVictory(reason) | GameEnd::BlackVictory(reason) | GameEnd::Draw(reason) => *reason
+        }
+    }
+}
+
+/// The different ways a game can end.
+/// Reference: https://www.chess.com/article/view/how-chess-games-can-end-8-ways-explained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEndReason {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    DeadPosition,  // neither side can ever checkmate, for a reason other than material (e.g. the pawns are all locked)
+    ThreefoldRepetition,  // claimable, which the bot always will
+    FivefoldRepetition,  // automatic
+    FiftyMoveRule,  // claimable, which the bot always will
+    SeventyFiveMoveRule,  // automatic
+    Resignation,
+    Timeout,
+    Agreement,
+    Abandonment
+}
+
+impl GameEndReason {
+    /// The value of the PGN Termination tag for a game which ended this way
+    pub fn pgn_termination(self: &Self) -> &'static str {
+        match self {
+            GameEndReason::Timeout => "time forfeit",
+            GameEndReason::Abandonment => "abandoned",
+            _ => "normal"
+        }
+    }
+}
+
+impl fmt::Display for GameEndReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            GameEndReason::Checkmate => "Checkmate",
+            GameEndReason::Stalemate => "Stalemate",
+            GameEndReason::InsufficientMaterial => "Insufficient material",
+            GameEndReason::DeadPosition => "Dead position",
+            GameEndReason::ThreefoldRepetition => "Threefold repetition",
+            GameEndReason::FivefoldRepetition => "Fivefold repetition",
+            GameEndReason::FiftyMoveRule => "Fifty-move rule",
+            GameEndReason::SeventyFiveMoveRule => "Seventy-five-move rule",
+            GameEndReason::Resignation => "Resignation",
+            GameEndReason::Timeout => "Timeout",
+            GameEndReason::Agreement => "Agreement",
+            GameEndReason::Abandonment => "Abandonment",
+        };
+        write!(f, "{}", description)
+    }
 }
 
 /// For a given square, e.g. `a2`
@@ -79,7 +176,7 @@ pub fn name_to_index_pair(square_name: String) -> Result<(usize, usize), ChessEr
     let mut input_chars = input_clone.chars();
 
     let column_letter = input_chars.next().unwrap();
-    if column_letter > 'h' || column_letter < 'a' {
+    if !('a'..='h').contains(&column_letter) {
         return Err(ChessError::InvalidArgument(format!(
             "Invalid column reference '{}', must be between 'a' and 'h'",
             column_letter
@@ -88,7 +185,7 @@ pub fn name_to_index_pair(square_name: String) -> Result<(usize, usize), ChessEr
     let column_index = 7 + column_letter as usize - 'h' as usize;
 
     let row_number = input_chars.next().unwrap();
-    if row_number > '8' || row_number < '1' {
+    if !('1'..='8').contains(&row_number) {
         return Err(ChessError::InvalidArgument(format!(
             "Invalid row reference '{}', must be between '1' and '8'",
             row_number
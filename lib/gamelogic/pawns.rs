@@ -0,0 +1,136 @@
+use core::fmt;
+
+use itertools::Itertools;
+
+use super::{bitboard::{self, Bitboard, EMPTY, FILE_A}, board::ChessBoard, index_pair_to_name, pieces::PieceType, Side};
+
+
+/// The features of one side's pawns. Squares are (column, row) and files are column numbers.
+/// Reference: https://www.chessprogramming.org/Pawn_Structure
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PawnStructure {
+    pub passed: Vec<(usize, usize)>,  // no opponent pawns ahead on the same or adjacent files can stop them
+    pub isolated: Vec<(usize, usize)>,  // no friendly pawns on the adjacent files
+    pub doubled: Vec<(usize, usize)>,  // every pawn on a file with more than one friendly pawn
+    pub backward: Vec<(usize, usize)>,  // behind the pawns on the adjacent files so they can't defend it, and can't advance safely
+    pub connected: Vec<(usize, usize)>,  // next to or diagonally defended by / defending a friendly pawn
+    pub islands: Vec<Vec<usize>>,  // groups of files next to each other which have pawns, separated by files without
+    pub open_files: Vec<usize>,  // no pawns at all
+    pub half_open_files: Vec<usize>,  // no friendly pawns, but the opponent has some
+    pub outposts: Vec<(usize, usize)>,  // squares in the opponent's half defended by a pawn which no opponent pawn can ever attack
+}
+
+impl fmt::Display for PawnStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let squares = |list: &Vec<(usize, usize)>| list.iter().map(|s| index_pair_to_name(s.0, s.1).unwrap()).join(" ");
+        let files = |list: &Vec<usize>| list.iter().map(|c| ((b'a' + *c as u8) as char).to_string()).join("");
+        writeln!(f, "Passed: {}", squares(&self.passed))?;
+        writeln!(f, "Isolated: {}", squares(&self.isolated))?;
+        writeln!(f, "Doubled: {}", squares(&self.doubled))?;
+        writeln!(f, "Backward: {}", squares(&self.backward))?;
+        writeln!(f, "Connected: {}", squares(&self.connected))?;
+        writeln!(f, "Islands: {}", self.islands.iter().map(files).join(" "))?;
+        writeln!(f, "Open files: {}", files(&self.open_files))?;
+        writeln!(f, "Half-open files: {}", files(&self.half_open_files))?;
+        write!(f, "Outposts: {}", squares(&self.outposts))
+    }
+}
+
+
+/// Every square on the file
+fn file_mask(column: usize) -> Bitboard {
+    FILE_A << column
+}
+
+/// Every square on the files either side of the column
+fn adjacent_files_mask(column: usize) -> Bitboard {
+    let left = match column {
+        0 => EMPTY,
+        _ => file_mask(column - 1)
+    };
+    let right = match column {
+        7 => EMPTY,
+        _ => file_mask(column + 1)
+    };
+    left | right
+}
+
+/// Every square on the rows in front of the row, from the side's point of view
+fn rows_ahead_mask(side: Side, row: usize) -> Bitboard {
+    match side {
+        Side::White if row < 7 => !0 << ((row + 1) * 8),
+        Side::Black if row > 0 => !0 >> ((8 - row) * 8),
+        _ => EMPTY
+    }
+}
+
+
+impl ChessBoard {
+    /// Analyse the side's pawns and the files around them
+    pub fn analyze_pawns(self: &Self, side: Side) -> PawnStructure {
+        let own_pawns = self.get_piece_bitboard(side, PieceType::Pawn);
+        let opponent_pawns = self.get_piece_bitboard(!side, PieceType::Pawn);
+        let opponent_pawn_attacks = bitboard::positions(opponent_pawns).fold(EMPTY, |attacks, p| attacks | bitboard::pawn_attacks(!side, p));
+        let mut structure = PawnStructure::default();
+
+        for position in bitboard::positions(own_pawns) {
+            let (column, row) = position;
+            let ahead = rows_ahead_mask(side, row);
+            let adjacent_pawns = own_pawns & adjacent_files_mask(column);
+
+            if opponent_pawns & ahead & (file_mask(column) | adjacent_files_mask(column)) == EMPTY {
+                structure.passed.push(position);
+            }
+            if adjacent_pawns == EMPTY {
+                structure.isolated.push(position);
+            }
+            if (own_pawns & file_mask(column)).count_ones() > 1 {
+                structure.doubled.push(position);
+            }
+            // the neighbouring pawns are all further forward, and the square in front is covered by an opponent pawn
+            let stop_square = match side {
+                Side::White => (column, row + 1),
+                Side::Black => (column, row - 1),
+            };
+            if adjacent_pawns != EMPTY
+                && adjacent_pawns & !ahead == EMPTY
+                && opponent_pawn_attacks & bitboard::square_mask(stop_square) != EMPTY {
+                structure.backward.push(position);
+            }
+            let neighbour_rows = (row.saturating_sub(1)..=(row + 1).min(7)).fold(EMPTY, |rows, r| rows | bitboard::RANK_1 << (r * 8));
+            if adjacent_pawns & neighbour_rows != EMPTY {
+                structure.connected.push(position);
+            }
+        }
+
+        for column in 0..8 {
+            let has_own = own_pawns & file_mask(column) != EMPTY;
+            let has_opponent = opponent_pawns & file_mask(column) != EMPTY;
+            match (has_own, has_opponent) {
+                (false, false) => structure.open_files.push(column),
+                (false, true) => structure.half_open_files.push(column),
+                _ => ()
+            }
+            if has_own {
+                match structure.islands.last_mut() {
+                    Some(island) if island.last().is_some_and(|c| c + 1 == column) => island.push(column),
+                    _ => structure.islands.push(vec![column])
+                }
+            }
+        }
+
+        // outposts are on the 4th to 6th rows from the side's point of view
+        let outpost_rows = match side {
+            Side::White => 3..=5,
+            Side::Black => 2..=4,
+        };
+        let own_pawn_defends = bitboard::positions(own_pawns).fold(EMPTY, |defends, p| defends | bitboard::pawn_attacks(side, p));
+        for square in bitboard::positions(own_pawn_defends & !own_pawns) {
+            let could_be_attacked = opponent_pawns & rows_ahead_mask(side, square.1) & adjacent_files_mask(square.0) != EMPTY;
+            if outpost_rows.contains(&square.1) && !could_be_attacked {
+                structure.outposts.push(square);
+            }
+        }
+        structure
+    }
+}
@@ -0,0 +1,47 @@
+use super::{board::ChessBoard, ChessMove};
+
+
+impl ChessBoard {
+    /// Count every sequence of legal moves of the given length from the current position, to check move generation against known results.
+    /// https://www.chessprogramming.org/Perft_Results
+    pub fn perft(self: &Self, depth: usize) -> u64 {
+        let mut scratch_board = self.clone_without_history();
+        count_nodes(&mut scratch_board, depth)
+    }
+
+    /// Get the perft node count after each of the legal moves in the current position, which helps find the move a bug is under.
+    pub fn perft_divide(self: &Self, depth: usize) -> Vec<(ChessMove, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut scratch_board = self.clone_without_history();
+        self.get_all_moves(self.state.current_turn).into_iter()
+            .map(|m| {
+                let undo = scratch_board.make_move(&m).unwrap();
+                let nodes = count_nodes(&mut scratch_board, depth - 1);
+                scratch_board.unmake_move(undo);
+                (m, nodes)
+            })
+            .collect()
+    }
+}
+
+
+fn count_nodes(board: &mut ChessBoard, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.get_all_moves(board.state.current_turn);
+    // the moves are already legal, so there's no need to perform the moves at the last level
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.iter()
+        .map(|m| {
+            let undo = board.make_move(m).unwrap();
+            let nodes = count_nodes(board, depth - 1);
+            board.unmake_move(undo);
+            nodes
+        })
+        .sum()
+}
@@ -0,0 +1,268 @@
+use super::{board::ChessBoard, ChessError, ChessMove, GameEnd, Side, Variant};
+
+/// The tags every PGN game must have, in the order they must be written
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const STANDARD_START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const MAX_LINE_LENGTH: usize = 80;
+
+
+/// A single game from a Portable Game Notation file, i.e. the tags plus the moves of the main line.
+/// https://www.chessclub.com/help/PGN-spec
+#[derive(Clone, Debug)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,  // tag name and value, in the order they're written
+    pub moves: Vec<ChessMove>,
+}
+
+impl PgnGame {
+    /// Create a game from the moves performed on a board, using `?` for all the unknown tags
+    pub fn from_board(board: &ChessBoard, game_end: Option<&GameEnd>) -> Self {
+        let mut game = PgnGame {
+            tags: SEVEN_TAG_ROSTER.iter().map(|name| (name.to_string(), "?".to_string())).collect(),
+            moves: board.move_list.clone()
+        };
+        game.set_tag("Date", "????.??.??");
+        game.set_tag("Result", game_end_to_result(game_end));
+        if let Some(ending) = game_end {
+            game.set_tag("Termination", ending.reason().pgn_termination());
+        }
+        if board.variant == Variant::Chess960 {
+            game.set_tag("Variant", "Chess960");
+        }
+        // games which didn't start from the normal setup need the position they started from
+        let starting_fen = board.get_starting_board().to_forsyth_edwards();
+        if starting_fen != STANDARD_START_FEN || board.variant == Variant::Chess960 {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &starting_fen);
+        }
+        game
+    }
+
+    pub fn get_tag(self: &Self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Set the value of a tag, replacing the existing value or adding it to the end
+    pub fn set_tag(self: &mut Self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string()))
+        }
+    }
+
+    /// Get the game result, which is `1-0`, `0-1`, `1/2-1/2`, or `*` if unknown
+    pub fn get_result(self: &Self) -> &str {
+        self.get_tag("Result").unwrap_or("*")
+    }
+
+    /// Get the board the game started from, which is the normal setup unless there's a FEN tag
+    pub fn get_starting_board(self: &Self) -> Result<ChessBoard, ChessError> {
+        start_game(&self.tags)
+    }
+
+    /// Get the board with every move of the game performed and recorded
+    pub fn replay(self: &Self) -> Result<ChessBoard, ChessError> {
+        let mut board = self.get_starting_board()?;
+        for the_move in self.moves.iter() {
+            board.make_move(the_move)?;
+        }
+        Ok(board)
+    }
+
+    /// Get the board after each move of the game, starting with the board before the first move
+    pub fn replay_positions(self: &Self) -> Result<Vec<ChessBoard>, ChessError> {
+        let mut board = self.get_starting_board()?;
+        let mut positions = vec![board.clone()];
+        for the_move in self.moves.iter() {
+            board.make_move(the_move)?;
+            positions.push(board.clone());
+        }
+        Ok(positions)
+    }
+
+    /// Write the game in PGN export format, with the seven tag roster first and the moves in SAN.
+    pub fn to_pgn(self: &Self) -> Result<String, ChessError> {
+        let mut pgn = String::new();
+        let roster_tags = SEVEN_TAG_ROSTER.iter().map(|name| (name.to_string(), self.get_tag(name).unwrap_or("?").to_string()));
+        let other_tags = self.tags.iter().filter(|(n, _)| !SEVEN_TAG_ROSTER.contains(&n.as_str())).cloned();
+        for (name, value) in roster_tags.chain(other_tags) {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        pgn.push('\n');
+
+        // build up the move text and then wrap it into lines
+        let mut board = self.get_starting_board()?;
+        let mut tokens = Vec::new();
+        for (index, the_move) in self.moves.iter().enumerate() {
+            match board.state.current_turn {
+                Side::White => tokens.push(format!("{}.", board.state.fullmove_number)),
+                // if the first move is made by Black then say which move number it is
+                Side::Black if index == 0 => tokens.push(format!("{}...", board.state.fullmove_number)),
+                Side::Black => ()
+            }
+            tokens.push(the_move.to_san(&board));
+            board.make_move(the_move)?;
+        }
+        tokens.push(self.get_result().to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + token.len() + 1 > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            }
+            if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            pgn.push_str(&token);
+            line_length += token.len();
+        }
+        pgn.push('\n');
+        Ok(pgn)
+    }
+}
+
+
+/// The PGN result string for how a game ended, or `*` if it's still going
+pub fn game_end_to_result(game_end: Option<&GameEnd>) -> &'static str {
+    match game_end {
+        Some(GameEnd::WhiteVictory(_)) => "1-0",
+        Some(GameEnd::BlackVictory(_)) => "0-1",
+        Some(GameEnd::Draw(_)) => "1/2-1/2",
+        None => "*"
+    }
+}
+
+
+/// Parse all the games in PGN text. Comments, NAGs and variations are skipped over, only the main line moves are kept.
+pub fn parse_pgn(pgn: &str) -> Result<Vec<PgnGame>, ChessError> {
+    let mut games = Vec::new();
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut board: Option<ChessBoard> = None;  // the board for the current game once the movetext has started
+    let mut variation_depth = 0;
+
+    let mut chars = pgn.chars().peekable();
+    let mut at_line_start = true;
+    while let Some(c) = chars.next() {
+        let was_line_start = at_line_start;
+        at_line_start = c == '\n';
+        match c {
+            // lines starting with % are escaped and ignored
+            '%' if was_line_start => {
+                chars.by_ref().take_while(|c| *c != '\n').for_each(drop);
+                at_line_start = true;
+            },
+            '[' if variation_depth == 0 => {
+                // a tag after moves means the last game never had a result, so it's finished now
+                if let Some(finished_board) = board.take() {
+                    games.push(finish_game(std::mem::take(&mut tags), finished_board, None));
+                }
+                tags.push(parse_tag(&mut chars)?);
+            },
+            '{' => {
+                chars.by_ref().take_while(|c| *c != '}').for_each(drop);
+            },
+            ';' => {
+                chars.by_ref().take_while(|c| *c != '\n').for_each(drop);
+                at_line_start = true;
+            },
+            '(' => variation_depth += 1,
+            ')' => {
+                if variation_depth == 0 {
+                    return Err(ChessError::InvalidArgument("PGN has a ')' without a variation to close".to_string()));
+                }
+                variation_depth -= 1;
+            },
+            '$' => {
+                while chars.next_if(|c| c.is_ascii_digit()).is_some() {}
+            },
+            '*' if variation_depth == 0 => {
+                let finished_board = match board.take() {
+                    Some(b) => b,
+                    None => start_game(&tags)?
+                };
+                games.push(finish_game(std::mem::take(&mut tags), finished_board, Some("*")));
+            },
+            c if c.is_alphanumeric() => {
+                let mut symbol = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || "_+#=:-/!?".contains(*c)) {
+                    symbol.push(c);
+                }
+                if variation_depth > 0 || symbol.chars().all(|c| c.is_ascii_digit()) {
+                    // moves inside variations and move numbers aren't needed
+                    continue;
+                }
+                let mut current_board = match board.take() {
+                    Some(b) => b,
+                    None => start_game(&tags)?
+                };
+                if ["1-0", "0-1", "1/2-1/2"].contains(&symbol.as_str()) {
+                    games.push(finish_game(std::mem::take(&mut tags), current_board, Some(&symbol)));
+                    continue;
+                }
+                let the_move = current_board.parse_san(&symbol)
+                    .map_err(|e| ChessError::InvalidMove(format!("PGN move {} '{}' could not be played: {}", current_board.move_list.len() + 1, symbol, e)))?;
+                current_board.make_move(&the_move)?;
+                board = Some(current_board);
+            },
+            _ => ()
+        }
+    }
+
+    if variation_depth != 0 {
+        return Err(ChessError::InvalidArgument("PGN has a variation which is never closed".to_string()));
+    }
+    if let Some(finished_board) = board.take() {
+        games.push(finish_game(tags, finished_board, None));
+    } else if !tags.is_empty() {
+        games.push(finish_game(tags.clone(), start_game(&tags)?, None));
+    }
+    Ok(games)
+}
+
+/// Parse the rest of a tag pair after the opening `[`, e.g. `Event "F/S Return Match"]`
+fn parse_tag(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<(String, String), ChessError> {
+    let raw_name = chars.by_ref().take_while(|c| *c != '"').collect::<String>();
+    let name = raw_name.trim().to_string();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(ChessError::InvalidArgument(format!("PGN tag has an invalid name '{}'", raw_name)));
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('\\') => value.extend(chars.next()),
+            Some('"') => break,
+            Some(c) => value.push(c),
+            None => return Err(ChessError::InvalidArgument(format!("PGN tag '{}' value is never closed", name)))
+        }
+    }
+    if chars.by_ref().find(|c| !c.is_whitespace()) != Some(']') {
+        return Err(ChessError::InvalidArgument(format!("PGN tag '{}' is never closed", name)));
+    }
+    Ok((name, value))
+}
+
+/// Create the board a game starts from using its tags
+fn start_game(tags: &[(String, String)]) -> Result<ChessBoard, ChessError> {
+    let find_tag = |name: &str| tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+    let is_chess960 = find_tag("Variant").is_some_and(|v| ["chess960", "chess 960", "fischerandom"].contains(&v.to_lowercase().as_str()));
+    match (find_tag("FEN"), is_chess960) {
+        (Some(fen), true) => ChessBoard::from_forsyth_edwards_960_strict(fen),
+        (Some(fen), false) => ChessBoard::from_forsyth_edwards_strict(fen),
+        (None, true) => ChessBoard::new_chess960(518),
+        (None, false) => Ok(ChessBoard::new())
+    }
+}
+
+/// Create the game once all its moves are parsed, using the result at the end of the moves if there wasn't a Result tag
+fn finish_game(tags: Vec<(String, String)>, board: ChessBoard, result: Option<&str>) -> PgnGame {
+    let mut game = PgnGame {
+        tags,
+        moves: board.move_list
+    };
+    if let (None, Some(result)) = (game.get_tag("Result"), result) {
+        game.set_tag("Result", result);
+    }
+    game
+}
@@ -1,6 +1,5 @@
-use std::cmp::min;
-
-use super::{board::ChessBoard, ChessMove, MoveType, ChessError, index_pair_to_name, Side};
+use super::{board::ChessBoard, ChessMove, MoveFilter, MoveType, ChessError, index_pair_to_name, Side, Variant};
+use super::bitboard::{self, Bitboard};
 
 
 
@@ -22,6 +21,9 @@ pub enum PieceType {
     King
 }
 
+/// Every type of piece a pawn can be promoted to, most valuable first
+pub const PROMOTION_PIECE_TYPES: [PieceType; 4] = [PieceType::Queen, PieceType::Knight, PieceType::Rook, PieceType::Bishop];
+
 
 impl std::ops::Not for Side {
     type Output = Self;
@@ -35,9 +37,10 @@ impl std::ops::Not for Side {
 }
 
 
-impl ChessPiece {
+impl PieceType {
+    /// How many points of material the type of piece is worth
     pub fn get_material(self: &Self) -> usize {
-        match self.piece_type {
+        match self {
             PieceType::Pawn => 1,
             PieceType::Rook => 5,
             PieceType::Knight => 3,
@@ -46,38 +49,58 @@ impl ChessPiece {
             PieceType::King => 42, // TODO maybe re-examine if there's a better way to handle this given a King can never be captured.
         }
     }
+}
+
+
+impl ChessPiece {
+    pub fn get_material(self: &Self) -> usize {
+        self.piece_type.get_material()
+    }
 
     /// For a given piece, get a list of all possible moves the piece could make
     pub fn get_moves(self: &Self, board: &ChessBoard) -> Vec<ChessMove> {
-        let unfiltered_moves = match self.piece_type {
+        remove_self_checks(board, self.get_pseudo_legal_moves(board))
+    }
+
+    /// Get all the moves the piece could make without checking whether they leave the King in check
+    pub(crate) fn get_pseudo_legal_moves(self: &Self, board: &ChessBoard) -> Vec<ChessMove> {
+        self.get_filtered_pseudo_legal_moves(board, MoveFilter::All)
+    }
+
+    /// Get the moves of the given kind the piece could make without checking whether they leave the King in check.
+    /// Checking moves aren't filtered here, since whether a move checks depends on the rest of the board, see `ChessBoard::get_filtered_moves`.
+    pub(crate) fn get_filtered_pseudo_legal_moves(self: &Self, board: &ChessBoard, filter: MoveFilter) -> Vec<ChessMove> {
+        // the squares pieces other than pawns are allowed to move to
+        let targets = match filter {
+            MoveFilter::All | MoveFilter::Checks => !bitboard::EMPTY,
+            MoveFilter::Captures | MoveFilter::CapturesAndPromotions => board.get_side_occupancy(!self.side),
+            MoveFilter::Quiet => !board.get_occupancy()
+        };
+        match self.piece_type {
             PieceType::Pawn => {
-                get_pawn_moves(self, board)
+                get_pawn_moves(self, board, filter)
             },
             PieceType::Rook => {
-                get_rook_moves(self, board)
+                get_rook_moves(self, board, targets)
             },
             PieceType::Knight => {
-                get_knight_moves(self, board)
+                get_knight_moves(self, board, targets)
             },
             PieceType::Bishop => {
-                get_bishop_moves(self, board)
+                get_bishop_moves(self, board, targets)
             },
             PieceType::Queen => {
-                get_queen_moves(self, board)
+                get_queen_moves(self, board, targets)
             },
             PieceType::King => {
-                get_king_moves(self, board)
+                // castling is never a capture
+                let include_castling = !matches!(filter, MoveFilter::Captures | MoveFilter::CapturesAndPromotions);
+                get_king_moves(self, board, targets, include_castling)
             },
-        };
-
-        // for all the possible moves, remove any where actually performing said move would result in the king being threatened
-        unfiltered_moves.into_iter()
-            .filter(|m| {
-                !move_would_cause_self_check(board, m)
-            })
-            .collect()
+        }
     }
 
+    /// Get the valid move of this piece to the given square. Pawns moving to the last row will promote to a Queen.
     pub fn get_specific_move(self: &Self, board: &ChessBoard, desired_move: (usize, usize)) -> Result<ChessMove, ChessError> {
         let valid_moves = self.get_moves(board);
 
@@ -92,987 +115,259 @@ impl ChessPiece {
     }
 
     pub fn get_threats(self: &Self, board: &ChessBoard) -> Vec<(usize, usize)> {
+        bitboard::positions(self.get_threat_bitboard(board)).collect()
+    }
+
+    /// Get the bitboard of every square this piece threatens, i.e. could capture on if an opponent piece was there.
+    pub fn get_threat_bitboard(self: &Self, board: &ChessBoard) -> Bitboard {
         match self.piece_type {
-            PieceType::Pawn => {
-                get_pawn_threats(self, board)
-            },
-            PieceType::Rook => {
-                get_rook_threats(self, board)
-            },
-            PieceType::Knight => {
-                get_knight_threats(self, board)
-            },
-            PieceType::Bishop => {
-                get_bishop_threats(self, board)
-            },
-            PieceType::Queen => {
-                get_queen_threats(self, board)
-            },
-            PieceType::King => {
-                get_king_threats(self, board)
-            },
+            PieceType::Pawn => bitboard::pawn_attacks(self.side, self.position),
+            PieceType::Rook => bitboard::rook_attacks(self.position, board.get_occupancy()),
+            PieceType::Knight => bitboard::knight_attacks(self.position),
+            PieceType::Bishop => bitboard::bishop_attacks(self.position, board.get_occupancy()),
+            PieceType::Queen => bitboard::queen_attacks(self.position, board.get_occupancy()),
+            PieceType::King => bitboard::king_attacks(self.position),
         }
     }
 }
 
-impl Into<char> for ChessPiece {
-    fn into(self) -> char {
-        let c = match self.piece_type {
+impl From<PieceType> for char {
+    fn from(piece_type: PieceType) -> char {
+        match piece_type {
             PieceType::Pawn => 'P',
             PieceType::Rook => 'R',
             PieceType::Knight => 'N',
             PieceType::Bishop => 'B',
             PieceType::Queen => 'Q',
             PieceType::King => 'K',
-        };
-        match self.side {
-            Side::White => c,
-            Side::Black => char::from_u32((c as u32) + 32).unwrap()  // lazily lowercase since I know this is going to always be valid ascii, and we can just shift as per the table
         }
     }
 }
 
-fn move_would_cause_self_check(board: &ChessBoard, the_move: &ChessMove) -> bool {
-    // create a copy of the current board state where we can perform the move and then check the result.
-    let mut board_copy = board.clone();
-    board_copy.perform_move(the_move).unwrap();
-
-    // check if the King is in check for the side that just moved
-    let piece = board.get_square_by_index(the_move.from_square.0, the_move.from_square.1).expect(format!("Tried to get a piece at position {:?} but piece didn't exist", the_move.from_square).as_str());
-
-    board_copy.is_checked(piece.side)
-}
+impl TryFrom<char> for PieceType {
+    type Error = ChessError;
 
-fn get_pawn_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
-    let mut possible_moves = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    if piece.side == Side::White {
-        // double move only if on starting rank and both the squares ahead are not ocupied
-        if current_row == 1 && board.get_square_by_index(current_col, current_row + 1).is_none() && board.get_square_by_index(current_col, current_row + 2).is_none() {
-            let destination = (current_col, current_row + 2);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::DoubleAdvance,
-                captures: None
-            });
-        }
-        // otherwise move forward as long as space is not occupied
-        if board.get_square_by_index(current_col, current_row + 1).is_none() {
-            let destination = (current_col, current_row + 1);
-            let move_type = match destination.1 == 7 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: None
-            });
-        }
-        // check possible captures
-        // negative side capture -- not at edge of board and space is occupied by piece of opposing side
-        if current_col >= 1 && board.get_square_by_index(current_col - 1, current_row + 1).is_some() && board.get_square_by_index(current_col - 1, current_row + 1).unwrap().side != piece.side {
-            let destination = (current_col - 1, current_row + 1);
-            let move_type = match destination.1 == 7 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: Some(destination)
-            });
-        }
-        // positive side capture -- not at edge of board and space is occupied by piece of opposing side
-        if current_col <= 6 && board.get_square_by_index(current_col + 1, current_row + 1).is_some() && board.get_square_by_index(current_col + 1, current_row + 1).unwrap().side != piece.side {
-            let destination = (current_col + 1, current_row + 1);
-            let move_type = match destination.1 == 7 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: Some(destination)
-            });
-        }
-        // if in position for en passtant move, add it to the list
-        if board.state.en_passant_column.is_some() && current_row == 4 && current_col.abs_diff(board.state.en_passant_column.unwrap()) == 1 {
-            let destination = (board.state.en_passant_column.unwrap(), current_row + 1);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::EnPassant,
-                captures: Some((destination.0, destination.1 - 1))
-            });
+    /// Parse the letter for a piece type, in either case (e.g. 'N' or 'n' for a Knight)
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c.to_ascii_uppercase() {
+            'P' => Ok(PieceType::Pawn),
+            'R' => Ok(PieceType::Rook),
+            'N' => Ok(PieceType::Knight),
+            'B' => Ok(PieceType::Bishop),
+            'Q' => Ok(PieceType::Queen),
+            'K' => Ok(PieceType::King),
+            _ => Err(ChessError::InvalidArgument(format!("'{}' is not the letter for a type of piece", c)))
         }
     }
-    // Black pawn moves
-    else {
-        // double move only if on starting rank and both the squares ahead are not ocupied
-        if current_row == 6 && board.get_square_by_index(current_col, current_row - 1).is_none() && board.get_square_by_index(current_col, current_row - 2).is_none() {
-            let destination = (current_col, current_row - 2);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::DoubleAdvance,
-                captures: None
-            });
-        }
-        // otherwise move forward as long as space is not occupied
-        if board.get_square_by_index(current_col, current_row - 1).is_none() {
-            let destination = (current_col, current_row - 1);
-            let move_type = match destination.1 == 0 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: None
-            });
-        }
-        // check possible captures
-        // negative side capture -- not at edge of board and space is occupied by piece of opposing side
-        if current_col >= 1 && board.get_square_by_index(current_col - 1, current_row - 1).is_some() && board.get_square_by_index(current_col - 1, current_row - 1).unwrap().side != piece.side {
-            let destination = (current_col - 1, current_row - 1);
-            let move_type = match destination.1 == 0 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: Some(destination)
-            });
-        }
-        // positive side capture -- not at edge of board and space is occupied by piece of opposing side
-        if current_col <= 6 && board.get_square_by_index(current_col + 1, current_row - 1).is_some() && board.get_square_by_index(current_col + 1, current_row - 1).unwrap().side != piece.side {
-            let destination = (current_col + 1, current_row - 1);
-            let move_type = match destination.1 == 0 {
-                true => MoveType::Promotion,
-                false => MoveType::Standard
-            };
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type,
-                captures: Some(destination)
-            });
-        }
-        // if in position for en passtant move, add it to the list
-        if board.state.en_passant_column.is_some() && current_row == 3 && current_col.abs_diff(board.state.en_passant_column.unwrap()) == 1 {
-            let destination = (board.state.en_passant_column.unwrap(), current_row - 1);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::EnPassant,
-                captures: Some((destination.0, destination.1 + 1))
-            });
-        }
-    }
-    possible_moves
-}
-
-
-fn get_pawn_threats(piece: &ChessPiece, _board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    if current_row % 7 == 0 {
-        return threatened_squares;
-    }
-    if piece.side == Side::White {
-        if current_col > 0 {
-            threatened_squares.push((current_col - 1, current_row + 1));
-        }
-        if current_col < 7 {
-            threatened_squares.push((current_col + 1, current_row + 1));
-        }
-    } else {
-        if current_col > 0 {
-            threatened_squares.push((current_col - 1, current_row - 1));
-        }
-        if current_col < 7 {
-            threatened_squares.push((current_col + 1, current_row - 1));
-        }
-    }
-    threatened_squares
 }
 
-
-fn get_rook_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
-    let mut possible_moves = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    // add all possible squares in current row in the negative direction until end of board or intersection with another game piece
-    for col in (0..current_col).rev() {
-        if board.get_square_by_index(col, current_row).is_none() {
-            let destination = (col, current_row);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
-            });
-        } else {
-            if board.get_square_by_index(col, current_row).unwrap().side != piece.side {
-                let destination = (col, current_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break
-            } else {
-                break
-            }
-        }
-    }
-    // add all possible squares in current row in the positive direction until end of board or intersection with another game piece
-    for col in current_col+1..8 {
-        if board.get_square_by_index(col, current_row).is_none() {
-            let destination = (col, current_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: None
-                });
-        } else {
-            if board.get_square_by_index(col, current_row).unwrap().side != piece.side {
-                let destination = (col, current_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break
-            } else {
-                break
-            }
-        }
-    }
-    // add all possible squares in current column in the negative direction until end of board or intersection with another game piece
-    for row in (0..current_row).rev() {
-        if board.get_square_by_index(current_col, row).is_none() {
-            let destination = (current_col, row);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
-            });
-        } else {
-            if board.get_square_by_index(current_col, row).unwrap().side != piece.side {
-                let destination = (current_col, row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break
-            } else {
-                break
-            }
-        }
-    }
-    // add all possible squares in current column in the positive direction until end of board or intersection with another game piece
-    for row in current_row+1..8 {
-        if board.get_square_by_index(current_col, row).is_none() {
-            let destination = (current_col, row);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
-            });
-        } else {
-            if board.get_square_by_index(current_col, row).unwrap().side != piece.side {
-                let destination = (current_col, row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break
-            } else {
-                break
-            }
+impl From<ChessPiece> for char {
+    fn from(piece: ChessPiece) -> char {
+        let c = char::from(piece.piece_type);
+        match piece.side {
+            Side::White => c,
+            Side::Black => char::from_u32((c as u32) + 32).unwrap()  // lazily lowercase since I know this is going to always be valid ascii, and we can just shift as per the table
         }
     }
-    // TODO handle castling
-    possible_moves
 }
 
-
-fn get_rook_threats(piece: &ChessPiece, board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    // add all possible squares in current row in the negative direction until end of board or intersection with another game piece
-    for col in (0..current_col).rev() {
-        if board.get_square_by_index(col, current_row).is_none() {
-            threatened_squares.push((col, current_row));
-        } else {
-            threatened_squares.push((col, current_row));
-            break
-        }
-    }
-    // add all possible squares in current row in the positive direction until end of board or intersection with another game piece
-    for col in current_col+1..8 {
-        if board.get_square_by_index(col, current_row).is_none() {
-            threatened_squares.push((col, current_row));
-        } else {
-            threatened_squares.push((col, current_row));
-            break
-        }
-    }
-    // add all possible squares in current column in the negative direction until end of board or intersection with another game piece
-    for row in (0..current_row).rev() {
-        if board.get_square_by_index(current_col, row).is_none() {
-            threatened_squares.push((current_col, row));
-        } else {
-            threatened_squares.push((current_col, row));
-            break
-        }
-    }
-    // add all possible squares in current column in the positive direction until end of board or intersection with a piece of th
-    for row in current_row+1..8 {
-        if board.get_square_by_index(current_col, row).is_none() {
-            threatened_squares.push((current_col, row));
-        } else {
-            threatened_squares.push((current_col, row));
-            break
-        }
-    }
-    threatened_squares
+/// For all the possible moves, remove any where actually performing said move would result in the king being threatened
+pub(crate) fn remove_self_checks(board: &ChessBoard, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+    if moves.is_empty() {
+        return moves;
+    }
+    // create a single copy of the current board state where we can try each move and then take it back
+    let mut scratch_board = board.clone_without_history();
+    moves.into_iter()
+        // a move which can't even be performed isn't legal either
+        .filter(|m| matches!(move_would_cause_self_check(&mut scratch_board, m), Ok(false)))
+        .collect()
 }
 
+fn move_would_cause_self_check(board: &mut ChessBoard, the_move: &ChessMove) -> Result<bool, ChessError> {
+    let undo = board.make_move(the_move)?;
 
-fn get_knight_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
-    let mut possible_moves = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-
-    // double left moves
-    if current_col > 1 {
-        // 2 left, 1 up
-        if current_row < 7 && (board.get_square_by_index(current_col - 2, current_row + 1).is_none() || board.get_square_by_index(current_col - 2, current_row + 1).unwrap().side != piece.side) {
-            let destination = (current_col - 2, current_row + 1);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-        // 2 left, 1 down
-        if current_row > 0 && (board.get_square_by_index(current_col - 2, current_row - 1).is_none() || board.get_square_by_index(current_col - 2, current_row - 1).unwrap().side != piece.side) {
-            let destination = (current_col - 2, current_row - 1);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-    }
-    // double right moves
-    if current_col < 6 {
-        // 2 right, 1 up
-        if current_row < 7 && (board.get_square_by_index(current_col + 2, current_row + 1).is_none() || board.get_square_by_index(current_col + 2, current_row + 1).unwrap().side != piece.side) {
-            let destination = (current_col + 2, current_row + 1);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-        // 2 left, 1 down
-        if current_row > 0 && (board.get_square_by_index(current_col + 2, current_row - 1).is_none() || board.get_square_by_index(current_col + 2, current_row - 1).unwrap().side != piece.side) {
-            let destination = (current_col + 2, current_row - 1);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-    }
-    // double up moves
-    if current_row < 6 {
-        // 2 up, 1 left
-        if current_col > 0 && (board.get_square_by_index(current_col - 1, current_row + 2).is_none() || board.get_square_by_index(current_col - 1, current_row + 2).unwrap().side != piece.side) {
-            let destination = (current_col - 1, current_row + 2);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-        // 2 up, 1 right
-        if current_col < 7 && (board.get_square_by_index(current_col + 1, current_row + 2).is_none() || board.get_square_by_index(current_col + 1, current_row + 2).unwrap().side != piece.side) {
-            let destination = (current_col + 1, current_row + 2);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-    }
-    // double down moves
-    if current_row > 1 {
-        // 2 down, 1 left
-        if current_col > 0 && (board.get_square_by_index(current_col - 1, current_row - 2).is_none() || board.get_square_by_index(current_col - 1, current_row - 2).unwrap().side != piece.side) {
-            let destination = (current_col - 1, current_row - 2);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-        // 2 down, 1 left
-        if current_col < 7 && (board.get_square_by_index(current_col + 1, current_row - 2).is_none() || board.get_square_by_index(current_col + 1, current_row - 2).unwrap().side != piece.side) {
-            let destination = (current_col + 1, current_row - 2);
-            let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: dest_capture
-            });
-        }
-    }
-    possible_moves
+    // check if the King is in check for the side that just moved
+    let is_checked = board.is_checked(undo.moved_piece.side);
+    board.unmake_move(undo);
+    Ok(is_checked)
 }
 
-
-fn get_knight_threats(piece: &ChessPiece, _board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-
-    // double left moves
-    if current_col > 1 {
-        // 2 left, 1 up
-        if current_row < 7 {
-                threatened_squares.push((current_col - 2, current_row + 1));
-        }
-        // 2 left, 1 down
-        if current_row > 0 {
-            threatened_squares.push((current_col - 2, current_row - 1));
-        }
-    }
-    // double right moves
-    if current_col < 6 {
-        // 2 right, 1 up
-        if current_row < 7 {
-                threatened_squares.push((current_col + 2, current_row + 1));
-        }
-        // 2 left, 1 down
-        if current_row > 0 {
-            threatened_squares.push((current_col + 2, current_row - 1));
-        }
-    }
-    // double up moves
-    if current_row < 6 {
-        // 2 up, 1 left
-        if current_col > 0 {
-                threatened_squares.push((current_col - 1, current_row + 2));
-        }
-        // 2 up, 1 right
-        if current_col < 7 {
-            threatened_squares.push((current_col + 1, current_row + 2));
-        }
-    }
-    // double down moves
-    if current_row > 1 {
-        // 2 down, 1 left
-        if current_col > 0 {
-                threatened_squares.push((current_col - 1, current_row - 2));
-        }
-        // 2 down, 1 left
-        if current_col < 7 {
-            threatened_squares.push((current_col + 1, current_row - 2));
-        }
-    }
-
-    threatened_squares
+/// Turn a bitboard of destination squares into moves, flagging any destination holding an opponent piece as a capture
+fn moves_to_targets(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard) -> Vec<ChessMove> {
+    let opponent_occupancy = board.get_side_occupancy(!piece.side);
+    bitboard::positions(targets & !board.get_side_occupancy(piece.side))
+        .map(|destination| ChessMove {
+            from_square: piece.position,
+            destination,
+            move_type: MoveType::Standard,
+            captures: match opponent_occupancy & bitboard::square_mask(destination) != bitboard::EMPTY {
+                true => Some(destination),
+                false => None
+            }
+        })
+        .collect()
 }
 
-
-fn get_bishop_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
+fn get_pawn_moves(piece: &ChessPiece, board: &ChessBoard, filter: MoveFilter) -> Vec<ChessMove> {
     let mut possible_moves = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-
-    // diagonal moves from current column/row up and to left
-    let up_left_count = min(current_col, 7 - current_row);
-    for diag_up_left in 1..=up_left_count {
-        let new_col = current_col - diag_up_left;
-        let new_row = current_row + diag_up_left;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        if board.get_square_by_index(new_col, new_row).is_none() {
-            let destination = (new_col, new_row);
+    let (include_captures, include_advances, include_promotions) = match filter {
+        MoveFilter::All | MoveFilter::Checks => (true, true, true),
+        MoveFilter::Captures => (true, false, false),
+        MoveFilter::CapturesAndPromotions => (true, false, true),
+        MoveFilter::Quiet => (false, true, false)
+    };
+    let (current_col, current_row) = piece.position;
+    let occupancy = board.get_occupancy();
+    // pawns only ever move "forward", which is up the board for White and down the board for Black
+    let (start_row, promotion_row, en_passant_row) = match piece.side {
+        Side::White => (1, 7, 4),
+        Side::Black => (6, 0, 3),
+    };
+    let forward = |row: usize, distance: usize| match piece.side {
+        Side::White => row + distance,
+        Side::Black => row - distance,
+    };
+    if current_row == promotion_row {
+        return possible_moves;
+    }
+    let single_advance = (current_col, forward(current_row, 1));
+    let single_advance_empty = occupancy & bitboard::square_mask(single_advance) == bitboard::EMPTY;
+
+    // double move only if on starting rank and both the squares ahead are not ocupied
+    if include_advances && current_row == start_row && single_advance_empty {
+        let destination = (current_col, forward(current_row, 2));
+        if occupancy & bitboard::square_mask(destination) == bitboard::EMPTY {
             possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
+                from_square: piece.position,
                 destination,
-                move_type: MoveType::Standard,
+                move_type: MoveType::DoubleAdvance,
                 captures: None
             });
-        } else {
-            if board.get_square_by_index(new_col, new_row).unwrap().side != piece.side {
-                let destination = (new_col, new_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break;
-            } else {
-                break;
-            }
         }
     }
-    // diagonal moves from current column/row up and to right
-    let up_right_count = min(7 - current_col, 7 - current_row);
-    for diag_up_right in 1..=up_right_count {
-        let new_col = current_col + diag_up_right;
-        let new_row = current_row + diag_up_right;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        if board.get_square_by_index(new_col, new_row).is_none() {
-            let destination = (new_col, new_row);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
-            });
-        } else {
-            if board.get_square_by_index(new_col, new_row).unwrap().side != piece.side {
-                let destination = (new_col, new_row);
+    // reaching the last row is a promotion, and the pawn can become any of the promotion piece types
+    let mut push_advance = |destination: (usize, usize), captures: Option<(usize, usize)>| {
+        if destination.1 == promotion_row {
+            // a promotion which captures is still wanted when only looking for captures
+            if !(include_promotions || (include_captures && captures.is_some())) {
+                return;
+            }
+            for promotion_type in PROMOTION_PIECE_TYPES {
                 possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
+                    from_square: piece.position,
                     destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
+                    move_type: MoveType::Promotion(promotion_type),
+                    captures
                 });
-                break;
-            } else {
-                break;
             }
-        }
-    }
-    // diagonal moves from current column/row down and to left
-    let down_left_count = min(current_col, current_row);
-    for diag_down_left in 1..=down_left_count {
-        let new_col = current_col - diag_down_left;
-        let new_row = current_row - diag_down_left;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        if board.get_square_by_index(new_col, new_row).is_none() {
-            let destination = (new_col, new_row);
-            possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
-            });
         } else {
-            if board.get_square_by_index(new_col, new_row).unwrap().side != piece.side {
-                let destination = (new_col, new_row);
             possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
+                from_square: piece.position,
                 destination,
                 move_type: MoveType::Standard,
-                captures: Some(destination)
+                captures
             });
-                break;
-            } else {
-                break;
-            }
         }
+    };
+    // otherwise move forward as long as space is not occupied
+    if single_advance_empty && (include_advances || (include_promotions && single_advance.1 == promotion_row)) {
+        push_advance(single_advance, None);
     }
-    // diagonal moves from current column/row down and to right
-    let down_right_count = min(7 - current_col, current_row);
-    for diag_down_right in 1..=down_right_count {
-        let new_col = current_col + diag_down_right;
-        let new_row = current_row - diag_down_right;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        if board.get_square_by_index(new_col, new_row).is_none() {
-            let destination = (new_col, new_row);
+    if !include_captures {
+        return possible_moves;
+    }
+    // check possible captures of any opponent piece which the pawn threatens
+    let captures = bitboard::pawn_attacks(piece.side, piece.position) & board.get_side_occupancy(!piece.side);
+    for destination in bitboard::positions(captures) {
+        push_advance(destination, Some(destination));
+    }
+    // if in position for en passtant move, add it to the list
+    if let Some(en_passant_column) = board.state.en_passant_column {
+        if current_row == en_passant_row && current_col.abs_diff(en_passant_column) == 1 {
             possible_moves.push(ChessMove {
-                from_square: (current_col, current_row),
-                destination,
-                move_type: MoveType::Standard,
-                captures: None
+                from_square: piece.position,
+                destination: (en_passant_column, forward(current_row, 1)),
+                move_type: MoveType::EnPassant,
+                captures: Some((en_passant_column, current_row))
             });
-        } else {
-            if board.get_square_by_index(new_col, new_row).unwrap().side != piece.side {
-                let destination = (new_col, new_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: Some(destination)
-                });
-                break;
-            } else {
-                break;
-            }
         }
     }
     possible_moves
 }
 
 
-fn get_bishop_threats(piece: &ChessPiece, board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-
-    // diagonal moves from current column/row up and to left
-    let up_left_count = min(current_col, 7 - current_row);
-    for diag_up_left in 1..=up_left_count {
-        let new_col = current_col - diag_up_left;
-        let new_row = current_row + diag_up_left;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        threatened_squares.push((new_col, new_row));
-        if board.get_square_by_index(new_col, new_row).is_some() {
-            break;
-        }
-    }
-    // diagonal moves from current column/row up and to right
-    let up_right_count = min(7 - current_col, 7 - current_row);
-    for diag_up_right in 1..=up_right_count {
-        let new_col = current_col + diag_up_right;
-        let new_row = current_row + diag_up_right;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        threatened_squares.push((new_col, new_row));
-        if board.get_square_by_index(new_col, new_row).is_some() {
-            break;
-        }
-    }
-    // diagonal moves from current column/row down and to left
-    let down_left_count = min(current_col, current_row);
-    for diag_down_left in 1..=down_left_count {
-        let new_col = current_col - diag_down_left;
-        let new_row = current_row - diag_down_left;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        threatened_squares.push((new_col, new_row));
-        if board.get_square_by_index(new_col, new_row).is_some() {
-            break;
-        }
-    }
-    // diagonal moves from current column/row down and to right
-    let down_right_count = min(7 - current_col, current_row);
-    for diag_down_right in 1..=down_right_count {
-        let new_col = current_col + diag_down_right;
-        let new_row = current_row - diag_down_right;
-        if new_col > 7 || new_row > 7 {
-            break;
-        }
-        threatened_squares.push((new_col, new_row));
-        if board.get_square_by_index(new_col, new_row).is_some() {
-            break;
-        }
-    }
-
-    threatened_squares
+fn get_rook_moves(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard) -> Vec<ChessMove> {
+    moves_to_targets(piece, board, bitboard::rook_attacks(piece.position, board.get_occupancy()) & targets)
 }
 
 
-fn get_queen_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
-    let mut possible_moves = Vec::new();
-    let mut diag_moves = get_bishop_moves(piece, board);
-    possible_moves.append(&mut diag_moves);
-    let mut hori_moves = get_rook_moves(piece, board);
-    possible_moves.append(&mut hori_moves);
-    possible_moves
+fn get_knight_moves(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard) -> Vec<ChessMove> {
+    moves_to_targets(piece, board, bitboard::knight_attacks(piece.position) & targets)
 }
 
 
-fn get_queen_threats(piece: &ChessPiece, board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let mut diag_threats = get_bishop_threats(piece, board);
-    threatened_squares.append(&mut diag_threats);
-    let mut hori_threats = get_rook_threats(piece, board);
-    threatened_squares.append(&mut hori_threats);
-    threatened_squares
+fn get_bishop_moves(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard) -> Vec<ChessMove> {
+    moves_to_targets(piece, board, bitboard::bishop_attacks(piece.position, board.get_occupancy()) & targets)
 }
 
 
-fn get_king_moves(piece: &ChessPiece, board: &ChessBoard) -> Vec<ChessMove> {
-    let mut possible_moves = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    // iterate over [0, 1, 2]
-    for col_shift in 0..3 {
-        for row_shift in 0..3 {
-            // ignore any moves which would move off the board
-            if (col_shift == 0 && current_col == 0) || (row_shift == 0 && current_row == 0) || (col_shift == 2 && current_col == 7) || (row_shift == 2 && current_row == 7) {
-                continue;
-            }
+fn get_queen_moves(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard) -> Vec<ChessMove> {
+    moves_to_targets(piece, board, bitboard::queen_attacks(piece.position, board.get_occupancy()) & targets)
+}
 
-            // ignore the center shift, as it's the current square
-            if col_shift == 1 && row_shift == 1 {
-                continue;
-            }
 
-            let new_col = current_col + col_shift - 1;
-            let new_row = current_row + row_shift - 1;
+fn get_king_moves(piece: &ChessPiece, board: &ChessBoard, targets: Bitboard, include_castling: bool) -> Vec<ChessMove> {
+    let mut possible_moves = moves_to_targets(piece, board, bitboard::king_attacks(piece.position) & targets);
+    if !include_castling {
+        return possible_moves;
+    }
+    let (current_col, current_row) = piece.position;
 
-            if board.get_square_by_index(new_col, new_row).is_none() {
-                let destination = (new_col, new_row);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: None
-                });
-            } else if board.get_square_by_index(new_col, new_row).unwrap().side != piece.side {
-                let destination = (new_col, new_row);
-                let dest_capture = board.get_square_by_index(destination.0, destination.1).map(|p| p.position);
-                possible_moves.push(ChessMove {
-                    from_square: (current_col, current_row),
-                    destination,
-                    move_type: MoveType::Standard,
-                    captures: dest_capture
-                });
-            }
-        }
+    // add castling moves, which are never allowed while in check
+    let (can_castle_queenside, can_castle_kingside) = match piece.side {
+        Side::White => (board.state.white_castle_queenside, board.state.white_castle_kingside),
+        Side::Black => (board.state.black_castle_queenside, board.state.black_castle_kingside),
+    };
+    if (!can_castle_queenside && !can_castle_kingside) || board.is_checked(piece.side) {
+        return possible_moves;
     }
-    // add castling moves
-    match piece.side {
-        Side::White => {
-            if !board.is_checked(Side::White) {
-                if board.state.white_castle_queenside {
-                    // check there's no pieces in the way AND the move wouldn't be a check
-                    let mut can_castle = true;
-                    for col in (1..current_col).rev() {
-                        if board.get_square_by_index(col, current_row).is_some() {
-                            can_castle = false;
-                            break
-                        }
-                        // Calculate intermediate moves and verify if the king were to take the move it wouldn't result in a check
-                        // b/c piece controls what moves are possible, the board allows us to jump king multiple spaces for testing :)
-                        let intermediate_move = ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (col, current_row),
-                            move_type: MoveType::Standard,
-                            captures: None
-                        };
-                        if move_would_cause_self_check(board, &intermediate_move) {
-                            can_castle = false;
-                            break
-                        }
-                    };
-                    // verify the piece in the castle's position is actually the castle of the correct side, and wasn't captured at some point.
-                    if can_castle {
-                        if board.get_square_by_index(0, current_row).is_some() {
-                            let rook_piece = board.get_square_by_index(0, current_row).unwrap();
-                            can_castle = (piece.side == rook_piece.side) && (rook_piece.piece_type == PieceType::Rook);
-                        } else {
-                            can_castle = false;
-                        }
-                    }
-                    // if all checks pass, we can castle
-                    if can_castle {
-                        possible_moves.push(ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (2, current_row),
-                            move_type: MoveType::Castle,
-                            captures: None
-                        });
-                    }
-                }
-                if board.state.white_castle_kingside {
-                    // check there's no pieces in the way
-                    let mut can_castle = true;
-                    for col in current_col+1..7 {
-                        if board.get_square_by_index(col, current_row).is_some() {
-                            can_castle = false;
-                            break
-                        }
-                        // Calculate intermediate moves and verify if the king were to take the move it wouldn't result in a check
-                        // b/c piece controls what moves are possible, the board allows us to jump king multiple spaces for testing :)
-                        let intermediate_move = ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (col, current_row),
-                            move_type: MoveType::Standard,
-                            captures: None
-                        };
-                        if move_would_cause_self_check(board, &intermediate_move) {
-                            can_castle = false;
-                            break
-                        }
-                    }
-                    // verify the piece in the castle's position is actually the castle of the correct side, and wasn't captured at some point.
-                    if can_castle {
-                        if board.get_square_by_index(7, current_row).is_some() {
-                            let rook_piece = board.get_square_by_index(7, current_row).unwrap();
-                            can_castle = (piece.side == rook_piece.side) && (rook_piece.piece_type == PieceType::Rook);
-                        } else {
-                            can_castle = false;
-                        }
-                    }
-                    if can_castle {
-                        possible_moves.push(ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (6, current_row),
-                            move_type: MoveType::Castle,
-                            captures: None
-                        });
-                    }
-                }
-            }
-        },
-        Side::Black => {
-            if !board.is_checked(Side::Black) {
-                if board.state.black_castle_queenside {
-                    // check there's no pieces in the way
-                    let mut can_castle = true;
-                    for col in (1..current_col).rev() {
-                        if board.get_square_by_index(col, current_row).is_some() {
-                            can_castle = false;
-                            break
-                        }
-                        // Calculate intermediate moves and verify if the king were to take the move it wouldn't result in a check
-                        // b/c piece controls what moves are possible, the board allows us to jump king multiple spaces for testing :)
-                        let intermediate_move = ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (col, current_row),
-                            move_type: MoveType::Standard,
-                            captures: None
-                        };
-                        if move_would_cause_self_check(board, &intermediate_move) {
-                            can_castle = false;
-                            break
-                        }
-                    }
-                    // verify the piece in the castle's position is actually the castle of the correct side, and wasn't captured at some point.
-                    if can_castle {
-                        if board.get_square_by_index(0, current_row).is_some() {
-                            let rook_piece = board.get_square_by_index(0, current_row).unwrap();
-                            can_castle = (piece.side == rook_piece.side) && (rook_piece.piece_type == PieceType::Rook);
-                        } else {
-                            can_castle = false;
-                        }
-                    }
-                    if can_castle {
-                        possible_moves.push(ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (2, current_row),
-                            move_type: MoveType::Castle,
-                            captures: None
-                        });
-                    }
-                }
-                if board.state.black_castle_kingside {
-                    // check there's no pieces in the way
-                    let mut can_castle = true;
-                    for col in current_col+1..7 {
-                        if board.get_square_by_index(col, current_row).is_some() {
-                            can_castle = false;
-                            break
-                        }
-                        // Calculate intermediate moves and verify if the king were to take the move it wouldn't result in a check
-                        // b/c piece controls what moves are possible, the board allows us to jump king multiple spaces for testing :)
-                        let intermediate_move = ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (col, current_row),
-                            move_type: MoveType::Standard,
-                            captures: None
-                        };
-                        if move_would_cause_self_check(board, &intermediate_move) {
-                            can_castle = false;
-                            break
-                        }
-                    }
-                    // verify the piece in the castle's position is actually the castle of the correct side, and wasn't captured at some point.
-                    if can_castle {
-                        if board.get_square_by_index(7, current_row).is_some() {
-                            let rook_piece = board.get_square_by_index(7, current_row).unwrap();
-                            can_castle = (piece.side == rook_piece.side) && (rook_piece.piece_type == PieceType::Rook);
-                        } else {
-                            can_castle = false;
-                        }
-                    }
-                    if can_castle {
-                        possible_moves.push(ChessMove {
-                            from_square: (current_col, current_row),
-                            destination: (6, current_row),
-                            move_type: MoveType::Castle,
-                            captures: None
-                        });
-                    }
-                }
-            }
-        },
+    for (has_right, kingside) in [(can_castle_queenside, false), (can_castle_kingside, true)] {
+        if !has_right {
+            continue;
+        }
+        // in Chess960 the rook can start anywhere, but the king and rook always end up on the same squares as standard castling
+        let rook_col = board.get_castling_rook_column(piece.side, kingside);
+        let (rook_dest_col, king_dest_col) = match kingside {
+            true => (5, 6),
+            false => (3, 2)
+        };
+        // verify the piece in the castle's position is actually the castle of the correct side, and wasn't captured at some point.
+        let rook_present = board.get_piece_bitboard(piece.side, PieceType::Rook) & bitboard::square_mask((rook_col, current_row)) != bitboard::EMPTY;
+        // check there's no pieces in the way of the king or rook, other than the king and rook themselves
+        let lowest_col = current_col.min(rook_col).min(king_dest_col).min(rook_dest_col);
+        let highest_col = current_col.max(rook_col).max(king_dest_col).max(rook_dest_col);
+        let path_clear = (lowest_col..=highest_col)
+            .filter(|col| *col != current_col && *col != rook_col)
+            .all(|col| board.get_square_by_index(col, current_row).is_none());
+        // AND the king wouldn't be threatened on any square it passes over or lands on. The rook is allowed to pass over threatened squares.
+        let king_path = current_col.min(king_dest_col)..=current_col.max(king_dest_col);
+        let path_safe = king_path.into_iter().filter(|col| *col != current_col).all(|col| !board.is_square_threatened(!piece.side, (col, current_row)));
+        // if all checks pass, we can castle
+        if rook_present && path_clear && path_safe {
+            // Chess960 castling is written as the king moving onto its own rook, since the king may not move at all or only one square
+            let destination_col = match board.variant {
+                Variant::Standard => king_dest_col,
+                Variant::Chess960 => rook_col
+            };
+            possible_moves.push(ChessMove {
+                from_square: piece.position,
+                destination: (destination_col, current_row),
+                move_type: MoveType::Castle,
+                captures: None
+            });
+        }
     }
 
     possible_moves
 }
-
-
-fn get_king_threats(piece: &ChessPiece, _board: &ChessBoard) -> Vec<(usize, usize)> {
-    let mut threatened_squares = Vec::new();
-    let current_col = piece.position.0;
-    let current_row = piece.position.1;
-    for col_shift in 0..3 {
-        for row_shift in 0..3 {
-            // ignore any moves which would move off the board
-            if (col_shift == 0 && current_col == 0) || (row_shift == 0 && current_row == 0) || (col_shift == 2 && current_col == 7) || (row_shift == 2 && current_row == 7) {
-                continue;
-            }
-
-            // ignore the center shift, as it's the current square
-            if col_shift == 1 && row_shift == 1 {
-                continue;
-            }
-
-            let new_col = current_col + col_shift - 1;
-            let new_row = current_row + row_shift - 1;
-
-            threatened_squares.push((new_col, new_row));
-        }
-    }
-    threatened_squares
-}
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::fmt::Display;
+
+use super::{bitboard, board::ChessBoard, pieces::{ChessPiece, PieceType}, ChessMove, Side};
+
+use colored::*;
+
+
+/// What a highlighted square on a rendered board means, which decides its background colour
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    Threat(Side),  // the square is attacked by the side. If both sides attack it, it's shown in a different colour.
+    Move,  // a piece can move to the square
+    LastMove,  // the last move was from or to the square
+    Check,  // a King in check
+}
+
+/// Draws a board in the terminal with highlighted squares, such as threat maps or where a piece can move to.
+/// Overlays added later are drawn over earlier ones.
+/// ```
+/// use chessbot_lib::gamelogic::{board::ChessBoard, render::BoardRenderer, Side};
+/// let board = ChessBoard::new();
+/// let knight = board.get_square_by_name("g1".to_string()).unwrap().unwrap();
+/// println!("{}", BoardRenderer::new(&board).threats(Side::Black).moves(&knight.get_moves(&board)).orientation(Side::Black));
+/// ```
+pub struct BoardRenderer<'a> {
+    board: &'a ChessBoard,
+    orientation: Side,  // the side at the bottom of the board
+    unicode: bool,  // use the chess piece glyphs, otherwise FEN letters for terminals which can't show them
+    colored: bool,
+    overlays: Vec<((usize, usize), Highlight)>
+}
+
+impl<'a> BoardRenderer<'a> {
+    /// Start with White at the bottom, Unicode pieces, colours on, and nothing highlighted
+    pub fn new(board: &'a ChessBoard) -> Self {
+        Self {
+            board,
+            orientation: Side::White,
+            unicode: true,
+            colored: true,
+            overlays: Vec::new()
+        }
+    }
+
+    /// Draw the board from the point of view of the side, so their pieces are at the bottom
+    pub fn orientation(mut self: Self, side: Side) -> Self {
+        self.orientation = side;
+        self
+    }
+
+    /// Use letters (upper case for White) instead of chess piece glyphs
+    pub fn ascii(mut self: Self, ascii: bool) -> Self {
+        self.unicode = !ascii;
+        self
+    }
+
+    /// Turn the colours off, so highlights aren't shown
+    pub fn colored(mut self: Self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    /// Highlight each of the squares
+    pub fn overlay(mut self: Self, squares: impl IntoIterator<Item = (usize, usize)>, highlight: Highlight) -> Self {
+        self.overlays.extend(squares.into_iter().map(|s| (s, highlight)));
+        self
+    }
+
+    /// Highlight every square the side attacks
+    pub fn threats(self: Self, side: Side) -> Self {
+        let threatened = self.board.get_threatened_map(side);
+        self.overlay(threatened, Highlight::Threat(side))
+    }
+
+    /// Highlight the destinations of the moves, e.g. from a piece's `get_moves`
+    pub fn moves(self: Self, moves: &[ChessMove]) -> Self {
+        self.overlay(moves.iter().map(|m| m.destination), Highlight::Move)
+    }
+
+    /// Highlight the from and destination squares of the last recorded move, if there is one
+    pub fn last_move(self: Self) -> Self {
+        match self.board.move_list.last() {
+            Some(m) => {
+                let squares = [m.from_square, m.destination];
+                self.overlay(squares, Highlight::LastMove)
+            },
+            None => self
+        }
+    }
+
+    /// Highlight the King of the side to move if it's in check
+    pub fn check(self: Self) -> Self {
+        let side = self.board.state.current_turn;
+        let king = bitboard::positions(self.board.get_piece_bitboard(side, PieceType::King)).next();
+        match king {
+            Some(position) if self.board.is_checked(side) => self.overlay([position], Highlight::Check),
+            _ => self
+        }
+    }
+
+    /// Get the two characters a square is drawn with
+    fn square_text(self: &Self, square: Option<ChessPiece>) -> &'static str {
+        let piece = match square {
+            Some(p) => p,
+            None => return match self.unicode {
+                true => "╶╴",
+                false => ". "
+            }
+        };
+        match (self.unicode, piece.side) {
+            (true, Side::White) => match piece.piece_type {
+                PieceType::Pawn => "♙ ",
+                PieceType::Rook => "♖ ",
+                PieceType::Knight => "♘ ",
+                PieceType::Bishop => "♗ ",
+                PieceType::Queen => "♕ ",
+                PieceType::King => "♔ ",
+            },
+            (true, Side::Black) => match piece.piece_type {
+                PieceType::Pawn => "♟︎ ",
+                PieceType::Rook => "♜ ",
+                PieceType::Knight => "♞ ",
+                PieceType::Bishop => "♝ ",
+                PieceType::Queen => "♛ ",
+                PieceType::King => "♚ ",
+            },
+            (false, Side::White) => match piece.piece_type {
+                PieceType::Pawn => "P ",
+                PieceType::Rook => "R ",
+                PieceType::Knight => "N ",
+                PieceType::Bishop => "B ",
+                PieceType::Queen => "Q ",
+                PieceType::King => "K ",
+            },
+            (false, Side::Black) => match piece.piece_type {
+                PieceType::Pawn => "p ",
+                PieceType::Rook => "r ",
+                PieceType::Knight => "n ",
+                PieceType::Bishop => "b ",
+                PieceType::Queen => "q ",
+                PieceType::King => "k ",
+            },
+        }
+    }
+
+    /// Colour the square using its highlights. The last highlight other than a threat wins, otherwise threats decide the colour.
+    fn color_square(self: &Self, text: ColoredString, position: (usize, usize)) -> ColoredString {
+        let highlights = self.overlays.iter().filter(|(s, _)| *s == position).map(|(_, h)| *h).collect::<Vec<Highlight>>();
+        match highlights.iter().rev().find(|h| !matches!(h, Highlight::Threat(_))) {
+            Some(Highlight::Check) => return text.on_red(),
+            Some(Highlight::LastMove) => return text.on_magenta(),
+            Some(Highlight::Move) => return text.on_yellow(),
+            _ => ()
+        }
+        let white_threat = highlights.contains(&Highlight::Threat(Side::White));
+        let black_threat = highlights.contains(&Highlight::Threat(Side::Black));
+        match (white_threat, black_threat) {
+            (true, true) => text.on_green(),
+            (true, false) => text.on_white(),
+            (false, true) => text.on_blue(),
+            (false, false) => text
+        }
+    }
+}
+
+impl Display for BoardRenderer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // rows are printed from the top, which for White is the last row
+        let (rows, columns): (Vec<usize>, Vec<usize>) = match self.orientation {
+            Side::White => ((0..8).rev().collect(), (0..8).collect()),
+            Side::Black => ((0..8).collect(), (0..8).rev().collect()),
+        };
+        for row in rows {
+            let label = format!("{} ", row + 1);
+            match self.colored {
+                true => write!(f, "{}", label.black())?,
+                false => write!(f, "{}", label)?
+            }
+            for col in columns.iter() {
+                let square = self.board.get_square_by_index(*col, row);
+                let text = self.square_text(square);
+                if !self.colored {
+                    write!(f, "{}", text)?;
+                    continue;
+                }
+                let colored_text = match square.map(|p| p.side) {
+                    Some(Side::White) => text.white(),
+                    Some(Side::Black) => text.blue(),
+                    None => text.truecolor(128, 128, 128)
+                };
+                write!(f, "{}", self.color_square(colored_text, (*col, row)))?;
+            }
+            writeln!(f)?;
+        }
+        let footer = columns.iter().map(|c| ((b'a' + *c as u8) as char).to_string()).collect::<Vec<String>>().join(" ");
+        match self.colored {
+            true => writeln!(f, "  {}", footer.black()),
+            false => writeln!(f, "  {}", footer)
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use super::{board::ChessBoard, pieces::PieceType, name_to_index_pair, index_pair_to_name, ChessError, ChessMove, MoveType};
+
+
+impl ChessMove {
+    /// Get the Standard Algebraic Notation for this move (e.g. `Nbd2`, `exd6`, `O-O`, `e8=N+`), which must be valid on the given board.
+    pub fn to_san(self: &Self, board: &ChessBoard) -> String {
+        let piece = board.get_square_by_position(self.from_square).unwrap_or_else(|| panic!("Tried to get SAN for a move without a piece to move: {:?}", self));
+        let destination = index_pair_to_name(self.destination.0, self.destination.1).unwrap();
+
+        let mut san = String::new();
+        if self.move_type == MoveType::Castle {
+            match self.destination.0 < self.from_square.0 {
+                true => san.push_str("O-O-O"),
+                false => san.push_str("O-O")
+            }
+        } else if piece.piece_type == PieceType::Pawn {
+            // pawn captures are named by the file the pawn came from
+            if self.captures.is_some() {
+                san.push(file_char(self.from_square.0));
+                san.push('x');
+            }
+            san.push_str(&destination);
+            if let Some(promoted_type) = self.promotion_piece() {
+                san.push('=');
+                san.push(promoted_type.into());
+            }
+        } else {
+            san.push(piece.piece_type.into());
+            // if another piece of the same type could also move to the destination, add the file and/or rank so it's clear which is moving
+            let others = board.get_all_moves(piece.side).into_iter()
+                .filter(|m| m.destination == self.destination && m.from_square != self.from_square)
+                .filter(|m| board.get_square_by_position(m.from_square).is_some_and(|p| p.piece_type == piece.piece_type))
+                .map(|m| m.from_square)
+                .collect::<Vec<(usize, usize)>>();
+            if !others.is_empty() {
+                if others.iter().all(|o| o.0 != self.from_square.0) {
+                    san.push(file_char(self.from_square.0));
+                } else if others.iter().all(|o| o.1 != self.from_square.1) {
+                    san.push(rank_char(self.from_square.1));
+                } else {
+                    san.push(file_char(self.from_square.0));
+                    san.push(rank_char(self.from_square.1));
+                }
+            }
+            if self.captures.is_some() {
+                san.push('x');
+            }
+            san.push_str(&destination);
+        }
+
+        // see if the move checks or checkmates the opponent
+        let mut after_move = board.clone_without_history();
+        after_move.perform_move(self).expect("Tried to get SAN for a move which couldn't be performed");
+        if after_move.is_checked(!piece.side) {
+            match after_move.get_all_moves(!piece.side).is_empty() {
+                true => san.push('#'),
+                false => san.push('+')
+            }
+        }
+        san
+    }
+}
+
+
+impl ChessBoard {
+    /// Find the legal move for the side to move matching the Standard Algebraic Notation (e.g. `Nf3`, `exd5`, `O-O-O`, `e8=Q+!?`)
+    pub fn parse_san(self: &Self, san: &str) -> Result<ChessMove, ChessError> {
+        // check/mate suffixes and annotation glyphs don't change what the move is
+        let trimmed = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        if trimmed.is_empty() {
+            return Err(ChessError::InvalidArgument(format!("SAN move is empty: '{}'", san)));
+        }
+        let legal_moves = self.get_all_moves(self.state.current_turn);
+
+        // castling is written the same no matter the side, and some sources use zeroes instead of the letter O
+        let castle_queenside = match trimmed {
+            "O-O" | "0-0" => Some(false),
+            "O-O-O" | "0-0-0" => Some(true),
+            _ => None
+        };
+        if let Some(queenside) = castle_queenside {
+            return legal_moves.into_iter()
+                .find(|m| m.move_type == MoveType::Castle && (m.destination.0 < m.from_square.0) == queenside)
+                .ok_or_else(|| ChessError::InvalidMove(format!("Castling is not a legal move: '{}'", san)));
+        }
+
+        let mut chars = trimmed.chars().collect::<Vec<char>>();
+        // an upper case first letter is the type of piece moving, otherwise it's a pawn
+        let piece_type = match chars[0].is_ascii_uppercase() {
+            true => PieceType::try_from(chars.remove(0))?,
+            false => PieceType::Pawn
+        };
+        // promotions are written like e8=Q, but accept e8Q too
+        let mut promotion_type = None;
+        if chars.last().is_some_and(|c| c.is_ascii_uppercase()) {
+            promotion_type = Some(PieceType::try_from(chars.pop().unwrap())?);
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+        if chars.len() < 2 {
+            return Err(ChessError::InvalidArgument(format!("SAN move has no destination square: '{}'", san)));
+        }
+        let destination = name_to_index_pair(chars.split_off(chars.len() - 2).into_iter().collect())?;
+
+        // whatever is left is the optional capture marker and the file and/or rank of the moving piece
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in chars.into_iter().filter(|c| *c != 'x') {
+            match c {
+                'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some(c as usize - '1' as usize),
+                _ => return Err(ChessError::InvalidArgument(format!("SAN move has an unexpected character '{}': '{}'", c, san)))
+            }
+        }
+
+        let mut candidates = legal_moves.into_iter()
+            .filter(|m| m.destination == destination && m.move_type != MoveType::Castle)
+            .filter(|m| self.get_square_by_position(m.from_square).is_some_and(|p| p.piece_type == piece_type))
+            .filter(|m| from_file.is_none_or(|f| m.from_square.0 == f) && from_rank.is_none_or(|r| m.from_square.1 == r))
+            .filter(|m| m.promotion_piece() == promotion_type)
+            .collect::<Vec<ChessMove>>();
+        match candidates.len() {
+            1 => Ok(candidates.remove(0)),
+            0 => Err(ChessError::InvalidMove(format!("'{}' is not a legal move in the current position", san))),
+            _ => Err(ChessError::InvalidMove(format!("'{}' is ambiguous, it could be any of {} moves", san, candidates.len())))
+        }
+    }
+}
+
+
+fn file_char(column: usize) -> char {
+    char::from_u32('a' as u32 + column as u32).unwrap()
+}
+
+fn rank_char(row: usize) -> char {
+    char::from_u32('1' as u32 + row as u32).unwrap()
+}
@@ -0,0 +1,145 @@
+use core::fmt;
+
+use itertools::Itertools;
+
+use super::{bitboard, board::ChessBoard, index_pair_to_name, pieces::{ChessPiece, PieceType}, Side};
+
+const ROOK_STEPS: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_STEPS: [(i64, i64); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+
+/// A tactical pattern on the board which one side can take advantage of. All the fields are the (column, row) squares of the pieces involved.
+/// Reference: https://www.chess.com/terms/chess-tactics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tactic {
+    /// The pinned piece can't move (if the pin is absolute, because the target is the King) or shouldn't move without exposing the more valuable target behind it
+    Pin { pinner: (usize, usize), pinned: (usize, usize), target: (usize, usize), absolute: bool },
+    /// A valuable piece is attacked and moving it out of the way exposes a less valuable piece behind it
+    Skewer { attacker: (usize, usize), front: (usize, usize), behind: (usize, usize) },
+    /// One piece attacks several pieces which are worth attacking at once
+    Fork { attacker: (usize, usize), targets: Vec<(usize, usize)> },
+    /// Moving the blocking piece out of the way lets the piece behind it attack the target
+    DiscoveredAttack { blocker: (usize, usize), attacker: (usize, usize), target: (usize, usize) },
+    /// The defender is the only thing protecting several attacked pieces, so it can't keep protecting them all
+    OverloadedDefender { defender: (usize, usize), defended: Vec<(usize, usize)> },
+}
+
+impl fmt::Display for Tactic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |square: &(usize, usize)| index_pair_to_name(square.0, square.1).unwrap();
+        let names = |squares: &Vec<(usize, usize)>| squares.iter().map(name).join(", ");
+        match self {
+            Tactic::Pin { pinner, pinned, target, absolute } => match absolute {
+                true => write!(f, "{} pins {} to the King on {}", name(pinner), name(pinned), name(target)),
+                false => write!(f, "{} pins {} to {}", name(pinner), name(pinned), name(target)),
+            },
+            Tactic::Skewer { attacker, front, behind } => write!(f, "{} skewers {} and {}", name(attacker), name(front), name(behind)),
+            Tactic::Fork { attacker, targets } => write!(f, "{} forks {}", name(attacker), names(targets)),
+            Tactic::DiscoveredAttack { blocker, attacker, target } => write!(f, "moving {} lets {} attack {}", name(blocker), name(attacker), name(target)),
+            Tactic::OverloadedDefender { defender, defended } => write!(f, "{} is the only defender of {}", name(defender), names(defended)),
+        }
+    }
+}
+
+
+impl ChessBoard {
+    /// Find all the tactics the side can take advantage of against its opponent, no matter whose turn it is
+    pub fn find_tactics(self: &Self, side: Side) -> Vec<Tactic> {
+        let mut tactics = Vec::new();
+        let pieces = self.get_all_pieces(side);
+        for piece in pieces.iter() {
+            tactics.extend(self.find_line_tactics(piece));
+            if let Some(fork) = self.find_fork(piece) {
+                tactics.push(fork);
+            }
+        }
+        tactics.extend(self.find_overloaded_defenders(side));
+        tactics
+    }
+
+    /// Check if attacking the opponent's piece on the square is a real threat, because it's the King, it's worth more than the attacker, or it's undefended
+    fn is_worth_attacking(self: &Self, attacker: &ChessPiece, target: &ChessPiece) -> bool {
+        target.piece_type == PieceType::King
+            || target.get_material() > attacker.get_material()
+            || self.get_attackers_bitboard(target.side, target.position) == bitboard::EMPTY
+    }
+
+    /// Pins, skewers and discovered attacks all come from looking along a sliding piece's lines at the first two pieces it would hit
+    fn find_line_tactics(self: &Self, slider: &ChessPiece) -> Vec<Tactic> {
+        let steps = match slider.piece_type {
+            PieceType::Rook => ROOK_STEPS.to_vec(),
+            PieceType::Bishop => BISHOP_STEPS.to_vec(),
+            PieceType::Queen => ROOK_STEPS.iter().chain(BISHOP_STEPS.iter()).copied().collect(),
+            _ => return Vec::new()
+        };
+        let mut tactics = Vec::new();
+        for step in steps {
+            let mut line = self.pieces_along_line(slider.position, step);
+            let (front, behind) = match (line.next(), line.next()) {
+                (Some(f), Some(b)) => (f, b),
+                _ => continue
+            };
+            if behind.side == slider.side {
+                continue;
+            }
+            if front.side == slider.side {
+                // the slider's own piece is in the way
+                if front.piece_type != PieceType::King && self.is_worth_attacking(slider, &behind) {
+                    tactics.push(Tactic::DiscoveredAttack { blocker: front.position, attacker: slider.position, target: behind.position });
+                }
+            } else if behind.piece_type == PieceType::King {
+                tactics.push(Tactic::Pin { pinner: slider.position, pinned: front.position, target: behind.position, absolute: true });
+            } else if front.piece_type == PieceType::King || front.get_material() > behind.get_material() {
+                tactics.push(Tactic::Skewer { attacker: slider.position, front: front.position, behind: behind.position });
+            } else if front.get_material() < behind.get_material() {
+                tactics.push(Tactic::Pin { pinner: slider.position, pinned: front.position, target: behind.position, absolute: false });
+            }
+        }
+        tactics
+    }
+
+    /// Get the pieces in a line from the square, in order of distance
+    fn pieces_along_line(self: &Self, from_square: (usize, usize), step: (i64, i64)) -> impl Iterator<Item = ChessPiece> + '_ {
+        let (mut col, mut row) = (from_square.0 as i64, from_square.1 as i64);
+        std::iter::from_fn(move || {
+            col += step.0;
+            row += step.1;
+            match (0..8).contains(&col) && (0..8).contains(&row) {
+                true => Some((col as usize, row as usize)),
+                false => None
+            }
+        }).filter_map(|square| self.get_square_by_position(square))
+    }
+
+    fn find_fork(self: &Self, attacker: &ChessPiece) -> Option<Tactic> {
+        let opponent_pieces = attacker.get_threat_bitboard(self) & self.get_side_occupancy(!attacker.side);
+        let targets = bitboard::positions(opponent_pieces)
+            .filter_map(|square| self.get_square_by_position(square))
+            .filter(|target| self.is_worth_attacking(attacker, target))
+            .map(|target| target.position)
+            .collect::<Vec<(usize, usize)>>();
+        match targets.len() >= 2 {
+            true => Some(Tactic::Fork { attacker: attacker.position, targets }),
+            false => None
+        }
+    }
+
+    /// Find the opponent's pieces which are the only defender of more than one of their pieces the side is attacking
+    fn find_overloaded_defenders(self: &Self, side: Side) -> Vec<Tactic> {
+        self.get_all_pieces(!side).into_iter()
+            .filter(|p| p.piece_type != PieceType::King && self.is_square_threatened(side, p.position))
+            .filter_map(|p| {
+                let mut defenders = bitboard::positions(self.get_attackers_bitboard(!side, p.position));
+                match (defenders.next(), defenders.next()) {
+                    (Some(defender), None) => Some((defender, p.position)),
+                    _ => None
+                }
+            })
+            .into_group_map()
+            .into_iter()
+            .filter(|(_, defended)| defended.len() >= 2)
+            .sorted()
+            .map(|(defender, defended)| Tactic::OverloadedDefender { defender, defended })
+            .collect()
+    }
+}
@@ -0,0 +1,45 @@
+use super::{board::ChessBoard, pieces::PieceType, name_to_index_pair, index_pair_to_name, ChessError, ChessMove};
+
+
+impl ChessMove {
+    /// Get the UCI long algebraic notation for this move, which is the from and destination squares plus the promotion piece (e.g. `e2e4`, `e7e8q`, `e1g1`)
+    pub fn to_uci(self: &Self) -> String {
+        let mut uci = format!(
+            "{}{}",
+            index_pair_to_name(self.from_square.0, self.from_square.1).unwrap(),
+            index_pair_to_name(self.destination.0, self.destination.1).unwrap()
+        );
+        if let Some(promoted_type) = self.promotion_piece() {
+            uci.push(char::from(promoted_type).to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
+
+impl ChessBoard {
+    /// Find the legal move for the side to move matching the UCI long algebraic notation (e.g. `e2e4`, `e7e8q`, `e1g1`)
+    pub fn parse_uci(self: &Self, uci: &str) -> Result<ChessMove, ChessError> {
+        let uci = uci.trim();
+        if !uci.is_ascii() || (uci.len() != 4 && uci.len() != 5) {
+            return Err(ChessError::InvalidMove(format!("UCI move must be two square names and an optional promotion piece, e.g. 'e7e8q': '{}'", uci)));
+        }
+        let from_square = name_to_index_pair(uci[0..2].to_string()).map_err(|e| ChessError::InvalidMove(format!("UCI move has an invalid from square '{}': {}", uci, e)))?;
+        let destination = name_to_index_pair(uci[2..4].to_string()).map_err(|e| ChessError::InvalidMove(format!("UCI move has an invalid destination '{}': {}", uci, e)))?;
+        let promotion_type = match uci.chars().nth(4) {
+            Some(c) if c.is_ascii_lowercase() => Some(PieceType::try_from(c).map_err(|e| ChessError::InvalidMove(format!("UCI move has an invalid promotion piece '{}': {}", uci, e)))?),
+            Some(c) => return Err(ChessError::InvalidMove(format!("UCI move promotion piece must be lower case '{}': '{}'", c, uci))),
+            None => None
+        };
+
+        let piece = match self.get_square_by_position(from_square) {
+            Some(p) if p.side == self.state.current_turn => p,
+            Some(_) => return Err(ChessError::InvalidMove(format!("'{}' moves a piece belonging to the side which isn't moving", uci))),
+            None => return Err(ChessError::InvalidMove(format!("'{}' moves from a square without a piece", uci))),
+        };
+        // the generated move has the correct move type and captured square for the position
+        piece.get_moves(self).into_iter()
+            .find(|m| m.destination == destination && m.promotion_piece() == promotion_type)
+            .ok_or_else(|| ChessError::InvalidMove(format!("'{}' is not a legal move in the current position", uci)))
+    }
+}
@@ -0,0 +1,141 @@
+use core::fmt;
+
+use super::{bitboard, board::ChessBoard, index_pair_to_name, pieces::PieceType, ChessError, Side, Variant};
+
+/// Something that makes a position impossible to reach in a legal game, or that the rest of the game logic can't handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionProblem {
+    MissingKing(Side),
+    TooManyKings(Side),
+    TooManyPieces(Side),  // more than the 16 pieces each side starts with
+    TooManyPawns(Side),
+    PawnOnLastRow((usize, usize)),  // pawns can never be on the first or last row
+    OpponentInCheck(Side),  // the side which isn't moving is in check, so the last move was illegal
+    CastlingWithoutKing(Side),  // has castling rights but the king isn't on its first row where it could castle
+    CastlingWithoutRook { side: Side, kingside: bool },
+    InvalidEnPassant(usize),  // the column doesn't have a pawn which could have just double advanced
+}
+
+impl fmt::Display for PositionProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionProblem::MissingKing(side) => write!(f, "{:?} has no King", side),
+            PositionProblem::TooManyKings(side) => write!(f, "{:?} has more than one King", side),
+            PositionProblem::TooManyPieces(side) => write!(f, "{:?} has more than 16 pieces", side),
+            PositionProblem::TooManyPawns(side) => write!(f, "{:?} has more than 8 Pawns", side),
+            PositionProblem::PawnOnLastRow(position) => write!(f, "there's a Pawn on {}", index_pair_to_name(position.0, position.1).unwrap()),
+            PositionProblem::OpponentInCheck(side) => write!(f, "{:?} is in check but it isn't their turn", side),
+            PositionProblem::CastlingWithoutKing(side) => write!(f, "{:?} can castle but their King isn't on a square it could castle from", side),
+            PositionProblem::CastlingWithoutRook { side, kingside } => match kingside {
+                true => write!(f, "{:?} can castle kingside but there's no Rook to castle with", side),
+                false => write!(f, "{:?} can castle queenside but there's no Rook to castle with", side),
+            },
+            PositionProblem::InvalidEnPassant(column) => write!(f, "there's no Pawn which could be captured en passant on the {} file", (b'a' + *column as u8) as char),
+        }
+    }
+}
+
+
+impl ChessBoard {
+    /// Check the position could happen in a legal game, returning every problem found. An empty list means the position is valid.
+    pub fn validate(self: &Self) -> Vec<PositionProblem> {
+        let mut problems = Vec::new();
+
+        for side in [Side::White, Side::Black] {
+            match self.get_piece_bitboard(side, PieceType::King).count_ones() {
+                0 => problems.push(PositionProblem::MissingKing(side)),
+                1 => (),
+                _ => problems.push(PositionProblem::TooManyKings(side))
+            }
+            if self.get_side_occupancy(side).count_ones() > 16 {
+                problems.push(PositionProblem::TooManyPieces(side));
+            }
+            let pawns = self.get_piece_bitboard(side, PieceType::Pawn);
+            if pawns.count_ones() > 8 {
+                problems.push(PositionProblem::TooManyPawns(side));
+            }
+            for position in bitboard::positions(pawns & (bitboard::RANK_1 | bitboard::RANK_8)) {
+                problems.push(PositionProblem::PawnOnLastRow(position));
+            }
+            problems.extend(self.validate_castling(side));
+        }
+
+        // checks can only be looked for if there's exactly one King each
+        let has_both_kings = [Side::White, Side::Black].iter().all(|side| self.get_piece_bitboard(*side, PieceType::King).count_ones() == 1);
+        if has_both_kings && self.is_checked(!self.state.current_turn) {
+            problems.push(PositionProblem::OpponentInCheck(!self.state.current_turn));
+        }
+
+        // the pawn which just double advanced must be in front of the en passant square, with the two squares it moved through empty
+        if let Some(column) = self.state.en_passant_column {
+            let mover = !self.state.current_turn;
+            let (pawn_row, skipped_row, start_row) = match mover {
+                Side::White => (3, 2, 1),
+                Side::Black => (4, 5, 6),
+            };
+            let is_valid = column <= 7
+                && self.get_square_by_index(column, pawn_row).is_some_and(|p| p.side == mover && p.piece_type == PieceType::Pawn)
+                && self.get_square_by_index(column, skipped_row).is_none()
+                && self.get_square_by_index(column, start_row).is_none();
+            if !is_valid {
+                problems.push(PositionProblem::InvalidEnPassant(column));
+            }
+        }
+
+        problems
+    }
+
+    /// Validate the position, turning any problems into an error listing all of them
+    pub fn ensure_valid(self: &Self) -> Result<(), ChessError> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let descriptions = problems.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+        Err(ChessError::InvalidState(format!("Position is not valid: {}", descriptions.join(", "))))
+    }
+
+    /// Parses a FEN string into a Board, and then makes sure the position is valid (see `validate`)
+    pub fn from_forsyth_edwards_strict(fen_string: String) -> Result<Self, ChessError> {
+        let board = Self::from_forsyth_edwards(fen_string)?;
+        board.ensure_valid()?;
+        Ok(board)
+    }
+
+    /// Parses a Chess960 FEN string into a Board, and then makes sure the position is valid (see `validate`)
+    pub fn from_forsyth_edwards_960_strict(fen_string: String) -> Result<Self, ChessError> {
+        let board = Self::from_forsyth_edwards_960(fen_string)?;
+        board.ensure_valid()?;
+        Ok(board)
+    }
+
+    /// Castling rights need the King on its first row, and the castling Rook on the correct side of the King
+    fn validate_castling(self: &Self, side: Side) -> Vec<PositionProblem> {
+        let (queenside, kingside, row) = match side {
+            Side::White => (self.state.white_castle_queenside, self.state.white_castle_kingside, 0),
+            Side::Black => (self.state.black_castle_queenside, self.state.black_castle_kingside, 7),
+        };
+        if !queenside && !kingside {
+            return Vec::new();
+        }
+        let king_column = (0..8).find(|col| self.get_square_by_index(*col, row).is_some_and(|p| p.side == side && p.piece_type == PieceType::King));
+        let king_column = match (king_column, self.variant) {
+            (Some(4), Variant::Standard) => 4,
+            (Some(col), Variant::Chess960) if (1..7).contains(&col) => col,
+            _ => return vec![PositionProblem::CastlingWithoutKing(side)]
+        };
+
+        let mut problems = Vec::new();
+        for (has_right, is_kingside) in [(queenside, false), (kingside, true)] {
+            if !has_right {
+                continue;
+            }
+            let rook_column = self.get_castling_rook_column(side, is_kingside);
+            let has_rook = self.get_square_by_index(rook_column, row).is_some_and(|p| p.side == side && p.piece_type == PieceType::Rook);
+            if !has_rook || (rook_column > king_column) != is_kingside {
+                problems.push(PositionProblem::CastlingWithoutRook { side, kingside: is_kingside });
+            }
+        }
+        problems
+    }
+}
@@ -0,0 +1,71 @@
+use super::Side;
+use super::bitboard;
+use super::pieces::PieceType;
+
+/// Random keys used to build Zobrist hashes of positions.
+/// A position's key is the XOR of the key for every piece on its square, plus keys for side to move, castling rights, and en passant.
+/// https://www.chessprogramming.org/Zobrist_Hashing
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+}
+
+lazy_static! {
+    static ref KEYS: ZobristKeys = {
+        // use a fixed seed so keys (and therefore hashes) are the same across every run of the program
+        let mut rng = SplitMix64(0x5EED_C4E5_5B07_2023);
+        let mut pieces = [[[0; 64]; 6]; 2];
+        for side_keys in pieces.iter_mut() {
+            for piece_keys in side_keys.iter_mut() {
+                for key in piece_keys.iter_mut() {
+                    *key = rng.next();
+                }
+            }
+        }
+        let black_to_move = rng.next();
+        let mut castling = [0; 4];
+        for key in castling.iter_mut() {
+            *key = rng.next();
+        }
+        let mut en_passant = [0; 8];
+        for key in en_passant.iter_mut() {
+            *key = rng.next();
+        }
+        ZobristKeys { pieces, black_to_move, castling, en_passant }
+    };
+}
+
+/// Small deterministic PRNG, good enough for generating hash keys.
+/// https://prng.di.unimi.it/splitmix64.c
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(self: &mut Self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+pub fn piece_key(side: Side, piece_type: PieceType, position: (usize, usize)) -> u64 {
+    KEYS.pieces[bitboard::side_index(side)][bitboard::piece_index(piece_type)][bitboard::square_index(position)]
+}
+
+/// Key which is included only when it's Black's turn
+pub fn black_to_move_key() -> u64 {
+    KEYS.black_to_move
+}
+
+/// Key for a castling right. Ordered as White kingside, White queenside, Black kingside, Black queenside.
+pub fn castling_key(index: usize) -> u64 {
+    KEYS.castling[index]
+}
+
+/// Key for the column of a pawn which can be captured en passant
+pub fn en_passant_key(column: usize) -> u64 {
+    KEYS.en_passant[column]
+}
@@ -1,8 +1,8 @@
 use std::time::Duration;
 
-use crate::{gamelogic::{board::ChessBoard, index_pair_to_name, GameEnd, MoveType, Side}, stratagems::Stratagem};
+use crate::{gamelogic::{board::ChessBoard, index_pair_to_name, pieces::PieceType, GameEnd, Side}, stratagems::Stratagem};
 
-use super::{Runner, RunnerError};
+use super::{save_game_pgn, Runner, RunnerError};
 
 use thirtyfour_sync::http::reqwest_sync::ReqwestDriverSync;
 use thirtyfour_sync::{prelude::*, GenericWebDriver};
@@ -12,6 +12,8 @@ use serde_json::json;
 // Used to control waiting for bot turn for webdriver.
 const WAIT_SECONDS: u64 = 600;
 const POLL_MILLIS: u64 = 250;
+// How many times in a row the opponent's move can fail to be read before the game is given up on
+const MAX_REFRESH_ATTEMPTS: usize = 20;
 
 pub struct ChessComGame {
     driver: GenericWebDriver<ReqwestDriverSync>,
@@ -19,7 +21,8 @@ pub struct ChessComGame {
     player_side: Side,
     player_bot: Box<dyn Stratagem>,
     current_turn: Side,
-    turn_number: usize
+    turn_number: usize,
+    pgn_path: Option<String>  // where to save the game once it's over
 }
 
 impl Runner for ChessComGame {
@@ -71,13 +74,22 @@ impl Runner for ChessComGame {
             player_side,
             player_bot,
             current_turn: Side::White,
-            turn_number: 0  // start at 0 since we're using it as an offset
+            turn_number: 0,  // start at 0 since we're using it as an offset
+            pgn_path: args.get(1).cloned()
         })
     }
 
     fn run_game(self: &mut Self) -> Result<GameEnd, RunnerError> {
+        let mut failed_refreshes = 0;
         loop {
             if let Some(v) = self.check_victory() {
+                if let Some(path) = &self.pgn_path {
+                    let (white, black) = match self.player_side {
+                        Side::White => ("ChessBot", "Opponent"),
+                        Side::Black => ("Opponent", "ChessBot"),
+                    };
+                    save_game_pgn(path, &self.board, &v, white, black)?;
+                }
                 println!("\nGAME OVER: {:?}\n\nPress enter to exit...", v);
                 let mut buf = String::new();
                 std::io::stdin().read_line(&mut buf).unwrap();
@@ -87,10 +99,20 @@ impl Runner for ChessComGame {
                 self.execute_bot_move().expect("Failed to perform bot move");
             } else {
                 self.wait_for_player_turn();
-                self.refresh_state().expect("Failed to refresh game state");
+                // the page may have been read part way through an animation, so look again rather than giving up on the game
+                if let Err(e) = self.refresh_state() {
+                    failed_refreshes += 1;
+                    if failed_refreshes >= MAX_REFRESH_ATTEMPTS {
+                        return Err(e);
+                    }
+                    eprintln!("Unable to read the opponent's move, trying again ({}/{}): {}", failed_refreshes, MAX_REFRESH_ATTEMPTS, e);
+                    std::thread::sleep(Duration::from_millis(POLL_MILLIS));
+                    continue;
+                }
+                failed_refreshes = 0;
             }
             if self.current_turn == Side::Black {
-                self.turn_number = self.turn_number + 1;
+                self.turn_number += 1;
                 eprintln!("We're now on turn {}", self.turn_number);
             }
         }
@@ -122,14 +144,28 @@ impl Runner for ChessComGame {
         };
 
         eprintln!("Opponent performed move {:?} to {:?}", index_pair_to_name(from_square.0, from_square.1).unwrap(), index_pair_to_name(to_square.0, to_square.1).unwrap());
-        eprintln!("FEN before opponent move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.get_board_state_hash());
-        let moved_piece = self.board.get_square_by_index(from_square.0, from_square.1).expect("Uhhh... the piece that's supposed to move doesn't exist");
-
-        let the_move = moved_piece.get_specific_move(&self.board, to_square).expect("Uhhh... the move that the opponent performed isn't in the list of valid moves.");
-        println!("Bot move: {:#?}", the_move);
-        self.board.perform_move_and_record(&the_move).expect("Unable to perform opponent move");
+        eprintln!("FEN before opponent move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.zobrist_key());
+        let moved_piece = self.board.get_square_by_index(from_square.0, from_square.1)
+            .ok_or_else(|| RunnerError::UnreadableStateError(format!("There is no piece on the highlighted square {:?} to move", from_square)))?;
+
+        let is_promotion = moved_piece.piece_type == PieceType::Pawn && (to_square.1 == 0 || to_square.1 == 7);
+        let the_move = match is_promotion {
+            true => {
+                // the opponent may have underpromoted, so see which piece ended up on the destination square
+                let promoted_type = self.read_piece_type(to_square).unwrap_or(PieceType::Queen);
+                moved_piece.get_moves(&self.board)
+                    .into_iter()
+                    .find(|m| m.destination == to_square && m.promotion_piece() == Some(promoted_type))
+                    .ok_or_else(|| RunnerError::InvalidStateError(format!("The opponent's promotion to {:?} isn't in the list of valid moves", to_square)))?
+            },
+            false => moved_piece.get_specific_move(&self.board, to_square)
+                .map_err(|e| RunnerError::InvalidStateError(format!("The opponent's move isn't in the list of valid moves: {}", e)))?
+        };
+        let san = the_move.to_san(&self.board);
+        self.board.try_apply(&the_move).map_err(|e| RunnerError::InvalidStateError(format!("Unable to perform opponent move {}: {}", san, e)))?;
+        println!("Opponent move: {}", san);
 
-        eprintln!("FEN after opponent move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.get_board_state_hash());
+        eprintln!("FEN after opponent move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.zobrist_key());
         println!("{}", self.board);
 
         self.current_turn = !self.current_turn;
@@ -141,7 +177,7 @@ impl Runner for ChessComGame {
         let bot_move = self.player_bot.get_move(&self.board);
         let from_classname = index_pair_to_class_name(bot_move.from_square);
         let to_classname = index_pair_to_class_name(bot_move.destination);
-        println!("Bot chose move from {} ({}) to {} ({})': {:#?}", index_pair_to_name(bot_move.from_square.0, bot_move.from_square.1).unwrap(), from_classname, index_pair_to_name(bot_move.destination.0, bot_move.destination.1).unwrap(), to_classname, bot_move);
+        println!("Bot chose move {} from {} ({}) to {} ({})", bot_move.to_san(&self.board), index_pair_to_name(bot_move.from_square.0, bot_move.from_square.1).unwrap(), from_classname, index_pair_to_name(bot_move.destination.0, bot_move.destination.1).unwrap(), to_classname);
 
         // with the chosen bot's move, perform it on chess.com and make sure it was actually performed.
         let from_square_element = self.driver.find_element(By::ClassName(&from_classname)).expect("Something went wrong -- unable to select FROM square");
@@ -162,15 +198,12 @@ impl Runner for ChessComGame {
             .perform()
             .expect("Unable to click TO square");
 
-        // handle clicking the button to promote to queen
-        if bot_move.move_type == MoveType::Promotion {
-            eprintln!("Attempting promotion");
+        // handle clicking the button for the piece to promote to
+        if let Some(promoted_type) = bot_move.promotion_piece() {
+            eprintln!("Attempting promotion to {:?}", promoted_type);
             // Since we're looking for an element with two CSS classes, use a . between the two classnames to select an element with both
-            let classname = match self.player_side {
-                Side::White => "promotion-piece.wq",
-                Side::Black => "promotion-piece.bq",
-            };
-            let promotion_element = self.driver.find_element(By::ClassName(classname)).expect("No promotion view while attempting to promote!?");
+            let classname = format!("promotion-piece.{}", piece_class_code(self.player_side, promoted_type));
+            let promotion_element = self.driver.find_element(By::ClassName(&classname)).expect("No promotion view while attempting to promote!?");
             self.driver.action_chain()
                 .move_to_element_center(&promotion_element)
                 .click()
@@ -179,10 +212,10 @@ impl Runner for ChessComGame {
         }
 
         eprintln!("Done with bot interaction, recording move");
-        self.board.perform_move_and_record(&bot_move).expect("Could not perform bot move");
+        self.board.try_apply(&bot_move).map_err(|e| RunnerError::InvalidStateError(format!("Could not perform bot move: {}", e)))?;
         self.current_turn = !self.current_turn;
 
-        eprintln!("FEN after bot move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.get_board_state_hash());
+        eprintln!("FEN after bot move: {} (hash: {})", self.board.to_forsyth_edwards(), self.board.zobrist_key());
         println!("{}", self.board);
 
         Ok(()) // If we've gotten this far, no errors
@@ -195,7 +228,7 @@ impl Runner for ChessComGame {
 }
 
 impl ChessComGame {
-    fn wait_for_player_turn(self: &Self) -> () {
+    fn wait_for_player_turn(self: &Self) {
         eprintln!("Waiting for player turn");
         match self.player_side {
             Side::White => {
@@ -213,12 +246,33 @@ impl ChessComGame {
         };
         eprintln!("Finished waiting for player turn");
     }
+
+    /// Read which type of piece is displayed on a square, using the piece code in its class name (e.g. `piece wn square-18`)
+    fn read_piece_type(self: &Self, square: (usize, usize)) -> Option<PieceType> {
+        let selector = format!(".piece.{}", index_pair_to_class_name(square));
+        let piece_element = self.driver.find_element(By::Css(&selector)).ok()?;
+        let class_name = piece_element.class_name().ok()??;
+        class_name.split_whitespace()
+            .find(|c| c.len() == 2 && (c.starts_with('w') || c.starts_with('b')))
+            .and_then(|code| code.chars().nth(1))
+            .and_then(|c| PieceType::try_from(c).ok())
+    }
+}
+
+
+/// The two letter code chess.com uses in class names for pieces, e.g. `wq` for the White Queen
+fn piece_class_code(side: Side, piece_type: PieceType) -> String {
+    let side_char = match side {
+        Side::White => 'w',
+        Side::Black => 'b',
+    };
+    format!("{}{}", side_char, char::from(piece_type).to_ascii_lowercase())
 }
 
 
 fn square_class_name_to_index_pair(name: &str) -> (usize, usize) {
     let classname_split: Vec<&str> = name.splitn(2, '-').collect();
-    let mut cell_iter = classname_split[1].chars().into_iter();
+    let mut cell_iter = classname_split[1].chars();
     let column = 7 + cell_iter.next().unwrap() as usize - '8' as usize;
     let row = 7 + cell_iter.next().unwrap() as usize - '8' as usize;
     let result = (column, row);
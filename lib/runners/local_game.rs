@@ -1,6 +1,6 @@
-use crate::{gamelogic::{board::ChessBoard, index_pair_to_name, GameEnd, Side}, stratagems::Stratagem};
+use crate::{gamelogic::{board::{ChessBoard, MoveUndo}, render::BoardRenderer, GameEnd, Side}, stratagems::Stratagem};
 
-use super::{Runner, RunnerError};
+use super::{save_game_pgn, Runner, RunnerError};
 
 use std::io::{stdin, stdout, Write};
 
@@ -9,26 +9,33 @@ pub struct LocalGame {
     pub board: ChessBoard,
     side: Side,
     bot_opponent: Box<dyn Stratagem>,
-    current_turn: Side
+    current_turn: Side,
+    history: Vec<MoveUndo>,  // undo info for every move made so far, to support takebacks
+    pgn_path: Option<String>  // where to save the game once it's over
 }
 
 
 impl Runner for LocalGame {
-    fn initialize<T: Stratagem + 'static>(_: Vec<String>) -> Result<Self, RunnerError>  where Self: Sized {
+    fn initialize<T: Stratagem + 'static>(args: Vec<String>) -> Result<Self, RunnerError>  where Self: Sized {
         let strat = <T as Stratagem>::initialize(Side::Black);
         Ok(LocalGame {
             board: ChessBoard::new(),
             side: Side::White, // player will always be White because that's easier for me to handle :)
             bot_opponent: Box::new(strat),  // The runner doesn't know, nor care, about the type of the Strategem, as long as the trait is implemented.
             current_turn: Side::White,
+            history: Vec::new(),
+            pgn_path: args.first().cloned()  // the only optional argument is a path to save the game's PGN to
         })
     }
 
     fn run_game(self: &mut Self) -> Result<GameEnd, RunnerError> {
         let mut bot_move = false;
         loop {
-            if self.check_victory().is_some() {
-                return Ok(self.check_victory().unwrap())
+            if let Some(game_end) = self.check_victory() {
+                if let Some(path) = &self.pgn_path {
+                    save_game_pgn(path, &self.board, &game_end, "Player", "ChessBot")?;
+                }
+                return Ok(game_end)
             }
             match bot_move {
                 true => {
@@ -45,10 +52,10 @@ impl Runner for LocalGame {
 
 
     fn refresh_state(self: &mut Self) -> Result<(), RunnerError> {
-        println!("Current Board State\n{}", self.board);
+        println!("Current Board State\n{}", BoardRenderer::new(&self.board).last_move().check().orientation(self.side));
         let user_move = 'outer: loop {
             let piece = loop {
-                print!("Enter a valid square for the game piece: ");
+                print!("Enter a valid square for the game piece (or a whole move, e.g. 'e2e4', or 'threats', 'hints', 'pawns' or 'takeback'): ");
                 let _ = stdout().flush();
                 let mut s=String::new();
                 stdin().read_line(&mut s).unwrap();
@@ -57,13 +64,59 @@ impl Runner for LocalGame {
                     println!("{:#?}", self.board.state);
                     continue 'outer;
                 }
+                if s == "hints" {
+                    for (title, side) in [("Tactics you can use", self.side), ("Tactics the bot can use", !self.side)] {
+                        let tactics = self.board.find_tactics(side);
+                        match tactics.is_empty() {
+                            true => println!("{}: none", title),
+                            false => println!("{}:", title)
+                        }
+                        for tactic in tactics {
+                            println!("  {}", tactic);
+                        }
+                    }
+                    continue 'outer;
+                }
+                if s == "pawns" {
+                    for (title, side) in [("Your pawns", self.side), ("The bot's pawns", !self.side)] {
+                        println!("{}:\n{}", title, self.board.analyze_pawns(side));
+                    }
+                    continue 'outer;
+                }
+                if s == "threats" {
+                    println!("Squares threatened by you (white), the bot (blue), or both (green)\n{}", self.board);
+                    continue 'outer;
+                }
+                if s == "takeback" {
+                    // take back both the bot's last move and the player's move before it
+                    if self.history.len() < 2 {
+                        println!("There are no moves of yours to take back");
+                        continue 'outer;
+                    }
+                    for _ in 0..2 {
+                        let undo = self.history.pop().unwrap();
+                        self.board.unmake_move(undo);
+                    }
+                    println!("Took back your last move\n{}", self.board);
+                    continue 'outer;
+                }
+                // anything longer than a square name is a whole move in UCI notation
+                if s.len() > 2 {
+                    match self.board.parse_uci(&s) {
+                        Ok(m) => break 'outer m,
+                        Err(e) => {
+                            println!("Invalid move: {}", e);
+                            continue 'outer;
+                        }
+                    }
+                }
                 let piece_res = self.board.get_square_by_name(s);
                 match piece_res {
                     Ok(square) => match square {
                         Some(p) => {
                             if p.side != self.side {
                                 let all_piece_moves = p.get_moves(&self.board);
-                                let valid_move_names = all_piece_moves.iter().map(|m| index_pair_to_name(m.destination.0, m.destination.1).unwrap()).collect::<Vec<String>>();
+                                let valid_move_names = all_piece_moves.iter().map(|m| m.to_san(&self.board)).collect::<Vec<String>>();
                                 println!("That piece doesn't belong to your side... but it's valid moves are: {:?}", valid_move_names);
                                 continue 'outer;
                             }
@@ -79,7 +132,8 @@ impl Runner for LocalGame {
                 println!("You can't move that piece -- there are no valid moves");
                 continue 'outer;
             }
-            let valid_move_names = all_piece_moves.iter().enumerate().map(|(index, m)| (index, index_pair_to_name(m.destination.0, m.destination.1).unwrap())).collect::<Vec<(usize, String)>>();
+            println!("{}", BoardRenderer::new(&self.board).moves(&all_piece_moves).check().orientation(self.side));
+            let valid_move_names = all_piece_moves.iter().enumerate().map(|(index, m)| (index, m.to_san(&self.board))).collect::<Vec<(usize, String)>>();
             let just_move_names = valid_move_names.iter().map(|(_, n)| n).collect::<Vec<&String>>();
             let chosen_move = loop {
                 println!("Valid Moves are: {:?}", just_move_names);
@@ -102,8 +156,9 @@ impl Runner for LocalGame {
         };
 
         // perform the move the user requested
-        self.board.perform_move_and_record(&user_move).expect("Could not perform player move");
-        println!("Board After Player Move:\n{}", self.board);
+        let undo = self.board.try_apply(&user_move).map_err(|e| RunnerError::InvalidStateError(format!("Could not perform player move: {}", e)))?;
+        self.history.push(undo);
+        println!("Board After Player Move:\n{}", BoardRenderer::new(&self.board).last_move().orientation(self.side));
         // get the bot move and perform it too
         self.current_turn = !self.current_turn;
         Ok(())
@@ -112,8 +167,9 @@ impl Runner for LocalGame {
 
     fn execute_bot_move(self: &mut Self) -> Result<(), RunnerError> {
         let bot_move = self.bot_opponent.get_move(&self.board);
-        println!("Bot chose move: {:#?}", bot_move);
-        self.board.perform_move_and_record(&bot_move).expect("Could not perform bot move");
+        println!("Bot chose move: {}", bot_move.to_san(&self.board));
+        let undo = self.board.try_apply(&bot_move).map_err(|e| RunnerError::InvalidStateError(format!("Could not perform bot move: {}", e)))?;
+        self.history.push(undo);
         self.current_turn = !self.current_turn;
         Ok(()) // the game is entirely managed by the internal board state, no external system needs to be interacted with
     }
@@ -122,4 +178,4 @@ impl Runner for LocalGame {
     fn check_victory(self: &Self) -> Option<GameEnd> {
         self.board.is_game_over(self.current_turn)
     }
-}
\ No newline at end of file
+}
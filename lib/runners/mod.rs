@@ -1,7 +1,7 @@
 use core::fmt;
 use std::error::Error;
 
-use crate::{gamelogic::GameEnd, stratagems::Stratagem};
+use crate::{gamelogic::{board::ChessBoard, pgn::PgnGame, GameEnd}, stratagems::Stratagem};
 
 pub mod local_game;
 pub mod chess_com;
@@ -14,7 +14,8 @@ pub enum RunnerError {
     ConnectionLost(String),
     UnreadableStateError(String),
     InvalidStateError(String),
-    InputLocked(String)
+    InputLocked(String),
+    SaveFailure(String)
 }
 
 
@@ -37,3 +38,12 @@ pub trait Runner {
     fn check_victory(self: &Self) -> Option<GameEnd>;
 }
 
+
+/// Write the game played on the board to a PGN file so it can be reviewed afterwards
+pub fn save_game_pgn(path: &str, board: &ChessBoard, game_end: &GameEnd, white: &str, black: &str) -> Result<(), RunnerError> {
+    let mut game = PgnGame::from_board(board, Some(game_end));
+    game.set_tag("White", white);
+    game.set_tag("Black", black);
+    let pgn = game.to_pgn().map_err(|e| RunnerError::SaveFailure(format!("Unable to write the game as PGN: {}", e)))?;
+    std::fs::write(path, pgn).map_err(|e| RunnerError::SaveFailure(format!("Unable to save PGN to '{}': {}", path, e)))
+}
@@ -1,19 +1,23 @@
 use itertools::Itertools;
 
-use crate::gamelogic::{board::ChessBoard, pieces::PieceType, ChessMove, name_to_index_pair, MoveType, Side, GameEnd};
+use crate::gamelogic::{board::ChessBoard, pieces::PieceType, ChessMove, MoveType, Side, GameEnd, GameEndReason};
 
-use super::Stratagem;
+use super::{evaluation::{Evaluator, KingSafetyEvaluator, PawnStructureEvaluator, Score, TaperedPieceSquareEvaluator, WeightedEvaluator}, Stratagem};
 
 #[derive(Debug)]
 enum GamePhase {
     Opening,
-    MainGame
+    MainGame,
+    Endgame  // once most of the pieces are gone and the King can safely come out to help
 }
 
+/// Once there's this little non-pawn material left (see `TaperedPieceSquareEvaluator::get_phase`), it's the endgame
+const ENDGAME_PHASE: Score = 6;
+
 #[derive(Debug)]
 struct PlannedMoveSequence {
     display_str: String,
-    move_list: Vec<Option<ChessMove>>
+    move_list: Vec<Option<String>>  // moves in SAN, or None if any move is fine
 }
 
 #[allow(dead_code)]
@@ -27,6 +31,7 @@ struct DetailedMove {
     causes_check: bool,
     game_end: Option<GameEnd>,
     capture_materials: usize,
+    exchange_value: i64,  // material won or lost once all the captures on the destination are done
     total_hanging_materials: i64,
     pre_num_threats: usize,
     post_num_threats: usize,
@@ -39,30 +44,19 @@ struct DetailedMove {
     player_total_materials: usize,
     opponent_total_materials: usize,
     controlled_squares: usize,
+    player_tactics: usize,  // pins, forks, etc. we could use after the move
+    opponent_tactics: usize,
+    positional_score: Score,  // how good the position is after the move, not counting material
 }
 
 impl From<&str> for PlannedMoveSequence {
     fn from(s: &str) -> Self {
-        let mut move_list = Vec::new();
-        let parts = s.split(",").collect::<Vec<&str>>();
-        for planned in parts.iter() {
-            match *planned {
-                "any" => {
-                    move_list.push(None);
-                },
-                _ => {
-                    let mut move_split = planned.split("->");
-                    let from_square = name_to_index_pair(move_split.next().unwrap().to_string()).unwrap();
-                    let destination = name_to_index_pair(move_split.next().unwrap().to_string()).unwrap();
-                    move_list.push(Some(ChessMove {
-                        from_square,
-                        destination,
-                        move_type: MoveType::Standard, // This doesn't matter, so just fudge the values
-                        captures: None
-                    }));
-                }
-            }
-        }
+        let move_list = s.split(',')
+            .map(|planned| match planned {
+                "any" => None,
+                san => Some(san.to_string())
+            })
+            .collect();
         Self {
             display_str: s.to_string(),
             move_list
@@ -70,27 +64,49 @@ impl From<&str> for PlannedMoveSequence {
     }
 }
 
+impl PlannedMoveSequence {
+    /// If the game so far has followed this sequence, get the next planned move
+    fn next_move(self: &Self, board_state: &ChessBoard) -> Option<ChessMove> {
+        let num_moves_performed = board_state.move_list.len();
+        // replay the game so far from the start, since SAN moves can only be understood in the position they're played in
+        let mut replay_board = ChessBoard::new();
+        for (planned, actual) in std::iter::zip(&self.move_list, &board_state.move_list) {
+            if let Some(san) = planned {
+                if replay_board.parse_san(san).ok().as_ref() != Some(actual) {
+                    return None;
+                }
+            }
+            replay_board.perform_move(actual).ok()?;
+        }
+        match self.move_list.get(num_moves_performed) {
+            Some(Some(san)) => board_state.parse_san(san).ok(),
+            _ => None
+        }
+    }
+}
+
 
 lazy_static! {
     static ref WHITE_PLANNED_OPENINGS: Vec<PlannedMoveSequence> = vec![
-        PlannedMoveSequence::from("e2->e4,e7->e5,c2->c3,any,d2->d4"),
-        PlannedMoveSequence::from("e2->e4,d7->d5,f2->f3"),
-        PlannedMoveSequence::from("e2->e4,d7->d5,d2->d3,f5->e4,d3->e4,any,f2->f3"),
-        PlannedMoveSequence::from("e2->e4,g8->f6,d2->d3"),
-        PlannedMoveSequence::from("e2->e4,any,d1->e2,any,d2->d3"),
+        PlannedMoveSequence::from("e4,e5,c3,any,d4"),
+        PlannedMoveSequence::from("e4,d5,f3"),
+        PlannedMoveSequence::from("e4,d5,d3,dxe4,dxe4,any,f3"),
+        PlannedMoveSequence::from("e4,Nf6,d3"),
+        PlannedMoveSequence::from("e4,any,Qe2,any,d3"),
     ];
     static ref BLACK_PLANNED_OPENINGS:Vec<PlannedMoveSequence> = vec![
-        PlannedMoveSequence::from("e2->e4,e7->e6,e4->e5,f7->f6"),
-        PlannedMoveSequence::from("e2->e4,e7->e6,any,d8->f6"),
-        PlannedMoveSequence::from("c2->c4,e7->e5"),
-        PlannedMoveSequence::from("any,d7->d5,any,e7->e6")
+        PlannedMoveSequence::from("e4,e6,e5,f6"),
+        PlannedMoveSequence::from("e4,e6,any,Qf6"),
+        PlannedMoveSequence::from("c4,e5"),
+        PlannedMoveSequence::from("any,d5,any,e6")
     ];
 }
 
 pub struct ColeMiner {
     player_side: Side,
     current_state: GamePhase,
-    opponent_row: usize
+    opponent_row: usize,
+    evaluator: WeightedEvaluator  // material is already handled by the other parts of the move ranking
 }
 
 impl Stratagem for ColeMiner {
@@ -107,50 +123,39 @@ impl Stratagem for ColeMiner {
                 7
             },
         };
-        ColeMiner { player_side: side, current_state: GamePhase::Opening , opponent_row}
+        let evaluator = WeightedEvaluator::new()
+            .with(1.0, PawnStructureEvaluator)
+            .with(1.0, KingSafetyEvaluator)
+            .with(0.5, TaperedPieceSquareEvaluator::load_or_default());
+        ColeMiner { player_side: side, current_state: GamePhase::Opening , opponent_row, evaluator }
     }
 
     fn get_move(self: &mut Self, board_state: &ChessBoard) -> ChessMove {
         match self.current_state {
             GamePhase::Opening => self.get_opening_moves(board_state),
-            GamePhase::MainGame => self.get_standard_game_moves(board_state)
+            GamePhase::MainGame => {
+                if TaperedPieceSquareEvaluator::get_phase(board_state) <= ENDGAME_PHASE {
+                    self.enter_endgame();
+                }
+                self.get_standard_game_moves(board_state)
+            },
+            GamePhase::Endgame => self.get_standard_game_moves(board_state)
         }
     }
 }
 
 impl ColeMiner {
     fn get_opening_moves(self: &mut Self, board_state: &ChessBoard) -> ChessMove {
-        // Figure out if the current moves of the game match one of the pre-generated move lists, and
-        let mut preplanned_move: Option<ChessMove> = None;
-        let num_moves_performed = board_state.move_list.len();
-        match self.player_side {
-            Side::White => {
-                for planned_sequence in WHITE_PLANNED_OPENINGS.iter() {
-                    // the planned sequence must be shorter or equal to how many moves have occured, otherwise we're in uncharted territory
-                    if planned_sequence.move_list.len() < num_moves_performed {
-                        break;
-                    }
-                    if std::iter::zip(&planned_sequence.move_list, &board_state.move_list).all(|(planned, actual)| planned.is_none() || planned.as_ref().unwrap() == actual) {
-                        println!("All according to the plan: {}", planned_sequence.display_str);
-                        preplanned_move = Some(planned_sequence.move_list[num_moves_performed].clone().unwrap());
-                        break;
-                    }
-                }
-            },
-            Side::Black => {
-                for planned_sequence in BLACK_PLANNED_OPENINGS.iter() {
-                    if planned_sequence.move_list.len() < num_moves_performed {
-                        break;
-                    }
-                    if std::iter::zip(&planned_sequence.move_list, &board_state.move_list).all(|(planned, actual)| planned.is_none() || planned.as_ref().unwrap() == actual) {
-                        println!("All according to the plan: {}", planned_sequence.display_str);
-                        eprintln!("{:#?}", planned_sequence);
-                        preplanned_move = Some(planned_sequence.move_list[num_moves_performed].clone().unwrap());
-                        break;
-                    }
-                }
-            }
-        }
+        // Figure out if the current moves of the game match one of the pre-generated move lists, and if so use the next move from it
+        let planned_openings = match self.player_side {
+            Side::White => WHITE_PLANNED_OPENINGS.iter(),
+            Side::Black => BLACK_PLANNED_OPENINGS.iter(),
+        };
+        let preplanned_move = planned_openings.into_iter().find_map(|planned_sequence| {
+            let next_move = planned_sequence.next_move(board_state)?;
+            println!("All according to the plan: {}", planned_sequence.display_str);
+            Some(next_move)
+        });
 
         match preplanned_move {
             Some(m)=> m,
@@ -169,6 +174,13 @@ impl ColeMiner {
         println!("#==============================================================================#");
     }
 
+    fn enter_endgame(self: &mut Self) {
+        self.current_state = GamePhase::Endgame;
+        println!("#==============================================================================#");
+        println!("|  IT'S THE ENDGAME, TIME FOR THE KING TO JOIN IN                              |");
+        println!("#==============================================================================#");
+    }
+
     fn get_detailed_moves(self: &Self, board_state: &ChessBoard) -> Vec<DetailedMove> {
         let mut detailed_moves = Vec::new();
 
@@ -176,54 +188,30 @@ impl ColeMiner {
         let opponent_pieces = board_state.get_all_pieces(!self.player_side);
         let opponent_king = opponent_pieces.iter().find(|p| p.piece_type == PieceType::King).unwrap();
 
+        // every move is tried out on the same copy of the board and then taken back
+        let mut eval_board = board_state.clone();
+
         for piece in all_player_pieces {
             let piece_moves = piece.get_moves(board_state);
             let threats = board_state.get_square_threats(!self.player_side, piece.position);
             let defends = board_state.get_square_threats(self.player_side, piece.position); // This is the defends BEFORE the move, so it should always be 1
 
             for m in piece_moves {
-                let mut eval_board = board_state.clone();
-                eval_board.perform_move_and_record(&m).unwrap();
+                let undo = eval_board.make_move(&m).unwrap();
                 let post_threats = eval_board.get_square_threats(!self.player_side, m.destination);
                 let post_defends = eval_board.get_square_threats(self.player_side, m.destination);
 
                 let pre_lowest_threatener = threats.iter().map(|p| p.get_material()).sorted().last();
                 let post_lowest_threatener = post_threats.iter().map(|p| p.get_material()).sorted().last();
 
-                let total_hanging_materials = board_state.get_all_pieces(self.player_side)
-                    .iter()
-                    .find( |piece| {
-                        let p_threats = eval_board.get_square_threats(!self.player_side, piece.position);
-                        let p_defends = eval_board.get_square_threats(self.player_side, piece.position);
-                        if p_threats.is_empty() {
-                            false
-                        } else if p_defends.is_empty() {
-                            true
-                        } else {
-                            p_threats.iter().map(|p| p.get_material()).sorted().last().unwrap() < piece.get_material()
-                        }
-                    })
-                    .map(|piece| {
-                        piece.get_material() as i64
-                    })
+                // how much the opponent could win by capturing each of our pieces after the move
+                let total_hanging_materials = eval_board.get_all_pieces(self.player_side)
                     .iter()
+                    .map(|piece| eval_board.get_exchange_loss(piece.position))
                     .sum::<i64>();
 
-                let is_hanging = if threats.is_empty() {
-                    false
-                } else if defends.is_empty() {
-                    true
-                } else {
-                    pre_lowest_threatener.unwrap() < piece.get_material()
-                };
-
-                let hangs_piece = if post_threats.is_empty() {
-                    false  // not hanging if there's no threats
-                } else if post_defends.is_empty() {
-                    true  // hangs if there is at least 1 threat and no defenders
-                } else {
-                    post_lowest_threatener.unwrap() < piece.get_material()  // also hanging if the threatener is cheaper than what they're threatening
-                };
+                let is_hanging = board_state.is_hanging(piece.position);
+                let hangs_piece = eval_board.is_hanging(m.destination);
 
                 detailed_moves.push(DetailedMove {
                     chess_move: m.clone(),
@@ -237,6 +225,7 @@ impl ColeMiner {
                         Some(cap) => board_state.get_square_by_position(cap).unwrap().get_material(),
                         None => 0
                     },
+                    exchange_value: board_state.static_exchange_eval(&m),
                     total_hanging_materials,
                     pre_num_threats: threats.len(),
                     post_num_threats: post_threats.len(),
@@ -248,8 +237,12 @@ impl ColeMiner {
                     king_distance_change: get_distance(m.from_square, opponent_king.position) as i64 - get_distance(m.destination, opponent_king.position) as i64,
                     player_total_materials: board_state.get_total_materials(self.player_side),
                     opponent_total_materials: board_state.get_total_materials(!self.player_side),
-                    controlled_squares: eval_board.get_threatened_map(self.player_side).len()
-                })
+                    controlled_squares: eval_board.get_threatened_map(self.player_side).len(),
+                    player_tactics: eval_board.find_tactics(self.player_side).len(),
+                    opponent_tactics: eval_board.find_tactics(!self.player_side).len(),
+                    positional_score: self.evaluator.evaluate(&eval_board, self.player_side)
+                });
+                eval_board.unmake_move(undo);
             }
         }
 
@@ -261,7 +254,7 @@ impl ColeMiner {
         let ranked_moves = all_possible_moves.into_iter().sorted_by_key(|m| self.rank_move(m, board_state)).collect_vec();
         let best_move = &ranked_moves[ranked_moves.len() -1];
         let bmr = self.rank_move(best_move, board_state);
-        eprintln!("Best move ranked as {}: {:#?}", bmr, best_move);
+        eprintln!("Best move {} ranked as {}: {:#?}", best_move.chess_move.to_san(board_state), bmr, best_move);
         best_move.chess_move.clone()
     }
 
@@ -269,26 +262,17 @@ impl ColeMiner {
         let row_change = the_move.chess_move.from_square.1 as i64 - the_move.chess_move.destination.1 as i64;
         let num_towards_row = 7 - self.opponent_row as i64 - row_change.abs();
 
-        let last_move = board_state.move_list.iter().nth(board_state.move_list.len() - 2).unwrap();
+        let last_move = &board_state.move_list[board_state.move_list.len() - 2];
         let is_undo_move = the_move.chess_move.from_square == last_move.destination;
 
         //let pre_threatened_mat_diff = the_move.pre_lowest_threatener.unwrap_or(the_move.piece_materials) as f64 - the_move.piece_materials as f64;
         let post_threatened_mat_diff = the_move.post_lowest_threatener.unwrap_or(the_move.piece_materials) as f64 - the_move.piece_materials as f64;
 
-        let material_gain = match the_move.post_num_threats != 0 {
-            true => the_move.capture_materials as i64 - the_move.piece_materials as i64,
-            false => the_move.capture_materials as i64,
-        };
-
-        let adjusted_total_hanging = match the_move.is_hanging {
-            true => the_move.total_hanging_materials - the_move.piece_materials as i64,
-            false => the_move.total_hanging_materials,
-        };
 
         let specific_move_bias = match the_move.chess_move.move_type {
             MoveType::DoubleAdvance => 0.25,
             MoveType::Castle => 20.00,  // Higher number to overcome bias against moving King
-            MoveType::Promotion => 7.50,
+            MoveType::Promotion(PieceType::Queen) => 7.50,
             _ => 0.00
         };
 
@@ -316,6 +300,8 @@ impl ColeMiner {
             PieceType::Knight => 0.40,
             PieceType::Bishop => 0.25,
             PieceType::Queen => 0.30,
+            // in the endgame the King is a strong piece, and the piece-square tables show it where to go
+            PieceType::King if matches!(self.current_state, GamePhase::Endgame) => 0.50,
             PieceType::King => {
                 // Avoid moving the king for no reason, and especially moving in a way which disabled castling
                 match self.player_side {
@@ -336,8 +322,12 @@ impl ColeMiner {
                 match ending {
                     GameEnd::WhiteVictory(_) => 999_999, // because of how the move is calculated, our move won't end in a victory unless we're that side
                     GameEnd::BlackVictory(_) => 999_999,
-                    GameEnd::Draw(_) => match the_move.player_total_materials > the_move.opponent_total_materials {
-                        true => -1_000,  // avoid drawing while winning
+                    GameEnd::Draw(reason) => match the_move.player_total_materials > the_move.opponent_total_materials {
+                        // avoid drawing while winning, especially by stalemating an opponent who has nothing left
+                        true => match reason {
+                            GameEndReason::Stalemate => -5_000,
+                            _ => -1_000
+                        },
                         false => 1_000,  // if losing, try drawing
                     },
                 }
@@ -348,12 +338,15 @@ impl ColeMiner {
         // If you're wondering where these numbers came from... I made them up and they're not based on any concrete methodology
         let score: f64 = ((num_towards_row * ((the_move.piece_type == PieceType::Pawn) as i64) + 1) as f64 * 4.25)  // Encourage advancing towards opponent side of board, doubly so for pawns
                        + (the_move.king_distance_change as f64 * 5.00)  // Encourage moving towards the king
-                       + (material_gain as f64 * 100.00)  // Encourage moves that result in material advantage, discourage moves that result in material loss
+                       + (the_move.exchange_value as f64 * 100.00)  // Encourage moves that result in material advantage, discourage moves that result in material loss
                        + (the_move.capture_materials as f64 * 45.00)  // Encourage trades
-                       + (adjusted_total_hanging as f64 * -20.00)  // Discourage leaving pieces hanging, even if not the active piece
+                       + (the_move.total_hanging_materials as f64 * -20.00)  // Discourage leaving pieces hanging, even if not the active piece
                        + (the_move.post_num_threats as f64 * 7.50)  // Encourage threatening as much as possible
                        + (post_threatened_mat_diff * 8.50 * ((the_move.post_num_defends > 0) as i32) as f64)  // Encourage adding new threats, but don't discourage removing threats
                        + (the_move.controlled_squares as f64 * 0.10)  // Encourage moves which result in more board control
+                       + (the_move.player_tactics as f64 * 12.00)  // Encourage setting up pins, forks, and other tactics
+                       + (the_move.opponent_tactics as f64 * -12.00)  // Discourage moves which leave the opponent with tactics
+                       + (the_move.positional_score as f64 * 0.75)  // Encourage good pawn structure, king safety, and piece placement
                        // boolean scaling values
                        + ((-40 * the_move.hangs_piece as i32) as f64 * the_move.piece_materials as f64)  // Discourage hanging pieces with scaling depending on value being hung
                        + (150 * the_move.is_hanging as i32) as f64  // Encourage moving hanging pieces
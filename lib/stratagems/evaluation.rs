@@ -0,0 +1,288 @@
+use itertools::Itertools;
+
+use crate::gamelogic::{bitboard, board::ChessBoard, pieces::PieceType, ChessError, Side};
+
+
+/// A score for a position in centipawns, i.e. hundredths of a pawn
+pub type Score = i64;
+
+
+/// Judges how good a position is for one side without looking at any moves, so searches can compare the positions they reach
+pub trait Evaluator {
+    /// Score the position for the given side, where positive is good for that side and negative is good for the opponent
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score;
+}
+
+
+/// Counts the material each side has, not including the Kings
+pub struct MaterialEvaluator;
+
+impl Evaluator for MaterialEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        let material = |side: Side| board.get_all_pieces(side).iter()
+            .filter(|piece| piece.piece_type != PieceType::King)
+            .map(|piece| piece.get_material() as Score * 100)
+            .sum::<Score>();
+        material(side) - material(!side)
+    }
+}
+
+
+/// Rewards pieces for standing on the squares they're generally best on, e.g. Knights in the center and Kings tucked in the corner.
+/// It uses the middlegame values of the tables `TaperedPieceSquareEvaluator::load_or_default` reads, no matter how many pieces are left.
+pub struct PieceSquareEvaluator;
+
+impl PieceSquareEvaluator {
+    /// The bonus for a piece of the side standing on the square
+    pub fn get_square_bonus(side: Side, piece_type: PieceType, position: (usize, usize)) -> Score {
+        LOADED_TAPERED_TABLES.get_square_bonus(side, piece_type, position, MAX_PHASE)
+    }
+}
+
+impl Evaluator for PieceSquareEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        let bonuses = |side: Side| board.get_all_pieces(side).iter()
+            .map(|piece| Self::get_square_bonus(side, piece.piece_type, piece.position))
+            .sum::<Score>();
+        bonuses(side) - bonuses(!side)
+    }
+}
+
+
+/// Counts the squares each side's pieces attack which aren't blocked by their own pieces, since pieces with more options are usually better.
+/// Pawns and Kings aren't counted.
+pub struct MobilityEvaluator;
+
+impl Evaluator for MobilityEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        let occupancy = board.get_occupancy();
+        let mobility = |side: Side| board.get_all_pieces(side).iter()
+            .map(|piece| match piece.piece_type {
+                PieceType::Knight => bitboard::knight_attacks(piece.position),
+                PieceType::Bishop => bitboard::bishop_attacks(piece.position, occupancy),
+                PieceType::Rook => bitboard::rook_attacks(piece.position, occupancy),
+                PieceType::Queen => bitboard::queen_attacks(piece.position, occupancy),
+                PieceType::Pawn | PieceType::King => bitboard::EMPTY
+            })
+            .map(|attacks| (attacks & !board.get_side_occupancy(side)).count_ones() as Score)
+            .sum::<Score>();
+        mobility(side) - mobility(!side)
+    }
+}
+
+
+/// Rewards Kings sheltered by their own pawns, and penalizes Kings with lots of the squares around them attacked
+pub struct KingSafetyEvaluator;
+
+impl KingSafetyEvaluator {
+    fn get_safety(board: &ChessBoard, side: Side) -> Score {
+        let king_position = match bitboard::positions(board.get_piece_bitboard(side, PieceType::King)).next() {
+            Some(position) => position,
+            None => return 0
+        };
+        // the pawns on the files around the King on the two rows in front of it
+        let (king_col, king_row) = king_position;
+        let shield_pawns = (king_col.saturating_sub(1)..=(king_col + 1).min(7))
+            .cartesian_product(1..=2)
+            .filter_map(|(col, distance)| match side {
+                Side::White => Some(king_row + distance).filter(|row| *row <= 7).map(|row| (col, row)),
+                Side::Black => king_row.checked_sub(distance).map(|row| (col, row))
+            })
+            .filter(|square| board.get_square_by_position(*square).is_some_and(|p| p.side == side && p.piece_type == PieceType::Pawn))
+            .count() as Score;
+        let king_zone = bitboard::king_attacks(king_position);
+        let attacked_squares = bitboard::positions(king_zone)
+            .filter(|square| board.is_square_threatened(!side, *square))
+            .count() as Score;
+        shield_pawns * 10 - attacked_squares * 10
+    }
+}
+
+impl Evaluator for KingSafetyEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        Self::get_safety(board, side) - Self::get_safety(board, !side)
+    }
+}
+
+
+/// Scores the pawn structure from `ChessBoard::analyze_pawns`, rewarding passed and connected pawns and penalizing weak ones
+pub struct PawnStructureEvaluator;
+
+impl Evaluator for PawnStructureEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        let structure_score = |side: Side| {
+            let pawns = board.analyze_pawns(side);
+            let weak_pawns = pawns.isolated.len() + pawns.doubled.len() + pawns.backward.len();
+            pawns.passed.len() as Score * 20 + pawns.connected.len() as Score * 5 - weak_pawns as Score * 10
+        };
+        structure_score(side) - structure_score(!side)
+    }
+}
+
+
+/// The tables `TaperedPieceSquareEvaluator` uses unless it's given others
+const DEFAULT_TAPERED_TABLES: &str = include_str!("../../data/piece_square_tables.json");
+/// The environment variable with the path to a file of tables to use instead of the defaults, so they can be tuned without recompiling
+pub const TAPERED_TABLES_VARIABLE: &str = "CHESSBOT_PIECE_SQUARE_TABLES";
+/// The game phase with all the pieces still on the board, where each Knight and Bishop counts 1, each Rook 2, and each Queen 4
+pub const MAX_PHASE: Score = 24;
+lazy_static! {
+    // the tables from `load`, read once so every evaluator made with `load_or_default` shares them and only warns about a bad file once
+    static ref LOADED_TAPERED_TABLES: TaperedPieceSquareEvaluator = TaperedPieceSquareEvaluator::load().unwrap_or_else(|e| {
+        eprintln!("Using the default piece-square tables: {}", e);
+        TaperedPieceSquareEvaluator::default()
+    });
+}
+const PIECE_NAMES: [(&str, PieceType); 6] = [
+    ("pawn", PieceType::Pawn),
+    ("knight", PieceType::Knight),
+    ("bishop", PieceType::Bishop),
+    ("rook", PieceType::Rook),
+    ("queen", PieceType::Queen),
+    ("king", PieceType::King),
+];
+
+/// Piece-square tables with separate middlegame and endgame values, blended by how much non-pawn material is left.
+/// This lets e.g. the King hide in the corner while there are pieces around to attack it, and come to the center once they're gone.
+/// The tables are read from JSON like `data/piece_square_tables.json`, which has a `middlegame` and an `endgame` object mapping each
+/// piece name to 8 rows of 8 bonuses, from White's point of view with the 8th rank first.
+/// https://www.chessprogramming.org/Tapered_Eval
+#[derive(Debug, Clone)]
+pub struct TaperedPieceSquareEvaluator {
+    middlegame: [[Score; 64]; 6],  // indexed by bitboard::piece_index and then square, laid out like the data file
+    endgame: [[Score; 64]; 6]
+}
+
+impl Default for TaperedPieceSquareEvaluator {
+    fn default() -> Self {
+        Self::from_json(DEFAULT_TAPERED_TABLES).expect("The built in piece-square tables are invalid")
+    }
+}
+
+impl TaperedPieceSquareEvaluator {
+    /// Use the tables from the file named by the `CHESSBOT_PIECE_SQUARE_TABLES` environment variable, or the defaults if it isn't set
+    pub fn load() -> Result<Self, ChessError> {
+        match std::env::var(TAPERED_TABLES_VARIABLE) {
+            Ok(path) => Self::from_file(&path),
+            Err(_) => Ok(Self::default())
+        }
+    }
+
+    /// Same as `load`, but falls back to the default tables if the file can't be used.
+    /// The file is only read the first time this is called, so changes to it or the environment variable after that are ignored.
+    pub fn load_or_default() -> Self {
+        LOADED_TAPERED_TABLES.clone()
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ChessError> {
+        let json = std::fs::read_to_string(path).map_err(|e| ChessError::InvalidArgument(format!("Unable to read piece-square tables from '{}': {}", path, e)))?;
+        Self::from_json(&json).map_err(|e| ChessError::InvalidArgument(format!("Piece-square tables in '{}' are invalid: {}", path, e)))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ChessError> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| ChessError::InvalidArgument(format!("Piece-square tables are not valid JSON: {}", e)))?;
+        Ok(TaperedPieceSquareEvaluator {
+            middlegame: parse_phase_tables(&value, "middlegame")?,
+            endgame: parse_phase_tables(&value, "endgame")?
+        })
+    }
+
+    /// How far from the endgame the position is, from `MAX_PHASE` with every piece on the board down to 0 with only Kings and pawns.
+    /// Promotions can make it go over `MAX_PHASE`, so it's capped.
+    pub fn get_phase(board: &ChessBoard) -> Score {
+        let phase = [Side::White, Side::Black].into_iter()
+            .flat_map(|side| board.get_all_pieces(side))
+            .map(|piece| match piece.piece_type {
+                PieceType::Knight | PieceType::Bishop => 1,
+                PieceType::Rook => 2,
+                PieceType::Queen => 4,
+                PieceType::Pawn | PieceType::King => 0
+            })
+            .sum::<Score>();
+        phase.min(MAX_PHASE)
+    }
+
+    /// The bonus for a piece of the side standing on the square in the given game phase, see `get_phase`
+    pub fn get_square_bonus(self: &Self, side: Side, piece_type: PieceType, position: (usize, usize), phase: Score) -> Score {
+        // Black's pieces use the tables flipped upside down
+        let table_row = match side {
+            Side::White => 7 - position.1,
+            Side::Black => position.1
+        };
+        let index = table_row * 8 + position.0;
+        let piece = bitboard::piece_index(piece_type);
+        (self.middlegame[piece][index] * phase + self.endgame[piece][index] * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Evaluator for TaperedPieceSquareEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        let phase = Self::get_phase(board);
+        let bonuses = |side: Side| board.get_all_pieces(side).iter()
+            .map(|piece| self.get_square_bonus(side, piece.piece_type, piece.position, phase))
+            .sum::<Score>();
+        bonuses(side) - bonuses(!side)
+    }
+}
+
+/// Read the table for every piece from the middlegame or endgame object
+fn parse_phase_tables(value: &serde_json::Value, phase_name: &str) -> Result<[[Score; 64]; 6], ChessError> {
+    let phase = value.get(phase_name)
+        .and_then(|phase| phase.as_object())
+        .ok_or_else(|| ChessError::InvalidArgument(format!("Piece-square tables are missing the '{}' object", phase_name)))?;
+    let mut tables = [[0; 64]; 6];
+    for (piece_name, piece_type) in PIECE_NAMES {
+        let rows = phase.get(piece_name)
+            .and_then(|rows| rows.as_array())
+            .filter(|rows| rows.len() == 8)
+            .ok_or_else(|| ChessError::InvalidArgument(format!("The {} '{}' piece-square table must be a list of 8 rows", phase_name, piece_name)))?;
+        for (row_index, row) in rows.iter().enumerate() {
+            let squares = row.as_array()
+                .filter(|squares| squares.len() == 8)
+                .ok_or_else(|| ChessError::InvalidArgument(format!("Row {} of the {} '{}' piece-square table must have 8 numbers", row_index + 1, phase_name, piece_name)))?;
+            for (column, square) in squares.iter().enumerate() {
+                tables[bitboard::piece_index(piece_type)][row_index * 8 + column] = square.as_i64()
+                    .ok_or_else(|| ChessError::InvalidArgument(format!("Row {} of the {} '{}' piece-square table has a value which isn't a whole number: {}", row_index + 1, phase_name, piece_name, square)))?;
+            }
+        }
+    }
+    Ok(tables)
+}
+
+
+/// Adds up the scores of other evaluators, each multiplied by its weight
+#[derive(Default)]
+pub struct WeightedEvaluator {
+    evaluators: Vec<(f64, Box<dyn Evaluator>)>
+}
+
+impl WeightedEvaluator {
+    pub fn new() -> Self {
+        WeightedEvaluator { evaluators: Vec::new() }
+    }
+
+    /// Add another evaluator, whose score is multiplied by the weight
+    pub fn with(mut self: Self, weight: f64, evaluator: impl Evaluator + 'static) -> Self {
+        self.evaluators.push((weight, Box::new(evaluator)));
+        self
+    }
+
+    /// All the evaluators with weights that work well together for a search
+    pub fn standard() -> Self {
+        WeightedEvaluator::new()
+            .with(1.0, MaterialEvaluator)
+            .with(1.0, TaperedPieceSquareEvaluator::load_or_default())
+            .with(2.0, MobilityEvaluator)
+            .with(1.0, KingSafetyEvaluator)
+            .with(1.0, PawnStructureEvaluator)
+    }
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn evaluate(self: &Self, board: &ChessBoard, side: Side) -> Score {
+        self.evaluators.iter()
+            .map(|(weight, evaluator)| weight * evaluator.evaluate(board, side) as f64)
+            .sum::<f64>()
+            .round() as Score
+    }
+}
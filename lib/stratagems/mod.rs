@@ -2,6 +2,9 @@ use crate::gamelogic::{board::ChessBoard, ChessMove, Side};
 
 pub mod random_aggro;
 pub mod cole_miner;
+pub mod evaluation;
+pub mod searcher;
+pub mod transposition;
 
 pub trait Stratagem {
     fn initialize(side: Side) -> Self where Self: Sized;
@@ -0,0 +1,350 @@
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+
+use crate::gamelogic::{board::ChessBoard, ChessMove, MoveFilter, Side};
+
+use super::{evaluation::{Evaluator, WeightedEvaluator}, transposition::{Bound, TranspositionTable}, Stratagem};
+
+/// The score for checkmating right now, which goes down by one for every move until the mate so quicker mates score higher
+pub const MATE_SCORE: i64 = 1_000_000;
+/// Any score this far from `MATE_SCORE` is a forced mate rather than an evaluation
+const MATE_THRESHOLD: i64 = MATE_SCORE - 1_000;
+const INFINITY: i64 = MATE_SCORE + 1;
+/// The size of the transposition table unless the Searcher is given another
+pub const DEFAULT_TABLE_MEGABYTES: usize = 64;
+/// How far quiescence search goes past the end of the main search, which only matters for long sequences of checks
+const MAX_QUIESCENCE_PLY: usize = 32;
+
+
+/// How far the search is allowed to go before it has to choose a move
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    pub max_depth: usize,  // in plies, i.e. moves by either side
+    pub time_limit: Option<Duration>  // the search stops part way through a depth if it runs out of time, and uses the last full depth
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        SearchLimits {
+            max_depth: 32,
+            time_limit: Some(Duration::from_secs(5))
+        }
+    }
+}
+
+
+/// The outcome of a search, from the point of view of the side to move
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub best_move: Option<ChessMove>,  // only None if there are no legal moves
+    pub score: i64,  // in centipawns, or +/- `MATE_SCORE` minus the number of plies until mate
+    pub depth: usize,  // the deepest search which finished
+    pub principal_variation: Vec<ChessMove>,  // the moves both sides are expected to play, starting with the best move
+    pub nodes: u64
+}
+
+impl SearchResult {
+    /// How many moves until the side to move gives mate, or a negative number if they're getting mated. None if there's no forced mate.
+    pub fn mate_in(self: &Self) -> Option<i64> {
+        match self.score.abs() >= MATE_THRESHOLD {
+            true => {
+                let moves = (MATE_SCORE - self.score.abs() + 1) / 2;
+                Some(self.score.signum() * moves)
+            },
+            false => None
+        }
+    }
+}
+
+
+/// Looks ahead with a negamax alpha-beta search, deepening one ply at a time until it runs out of depth or time.
+/// https://www.chessprogramming.org/Alpha-Beta
+pub struct Searcher {
+    player_side: Side,
+    limits: SearchLimits,
+    table: TranspositionTable,  // kept for the whole game, since a lot of the positions from the last move's search will come up again
+    evaluator: Box<dyn Evaluator>
+}
+
+impl Stratagem for Searcher {
+    fn initialize(side: Side) -> Self where Self : Sized {
+        println!("Searcher Strategem is active for side: {:?}", side);
+        Searcher::new(side, SearchLimits::default())
+    }
+
+    fn get_move(self: &mut Self, board_state: &ChessBoard) -> ChessMove {
+        let result = self.search(board_state);
+        let variation = san_line(board_state, &result.principal_variation);
+        let score = match result.mate_in() {
+            Some(moves) => format!("mate in {}", moves),
+            None => format!("{} centipawns", result.score)
+        };
+        eprintln!("Searched to depth {} ({} nodes), score {}: {}", result.depth, result.nodes, score, variation);
+        eprintln!("Transposition table: {}", self.table.get_stats());
+        result.best_move.unwrap_or_else(|| panic!("The {:?} Searcher was asked for a move but there are no legal moves", self.player_side))
+    }
+}
+
+impl Searcher {
+    pub fn new(side: Side, limits: SearchLimits) -> Self {
+        Searcher {
+            player_side: side,
+            limits,
+            table: TranspositionTable::new(DEFAULT_TABLE_MEGABYTES),
+            evaluator: Box::new(WeightedEvaluator::standard())
+        }
+    }
+
+    /// Score the positions at the end of the search with the given evaluator instead of `WeightedEvaluator::standard`
+    pub fn evaluator(mut self: Self, evaluator: impl Evaluator + 'static) -> Self {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Use a transposition table of roughly the given size instead of the default
+    pub fn table_size(mut self: Self, megabytes: usize) -> Self {
+        self.table = TranspositionTable::new(megabytes);
+        self
+    }
+
+    /// The transposition table, which has the statistics from the last search
+    pub fn get_table(self: &Self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// Find the best move for the side to move, searching one ply deeper at a time until the depth or time limit is reached
+    pub fn search(self: &mut Self, board_state: &ChessBoard) -> SearchResult {
+        self.table.new_search();
+        let mut context = SearchContext {
+            start: Instant::now(),
+            limits: self.limits,
+            nodes: 0,
+            stopped: false,
+            previous_pv: Vec::new(),
+            table: &mut self.table,
+            evaluator: self.evaluator.as_ref()
+        };
+        // keep the history so repeating a position from the game is seen as a draw
+        let mut board = board_state.clone();
+        let mut result = SearchResult {
+            best_move: board.get_all_moves(board.state.current_turn).into_iter().next(),
+            score: 0,
+            depth: 0,
+            principal_variation: Vec::new(),
+            nodes: 0
+        };
+        for depth in 1..=self.limits.max_depth.max(1) {
+            let mut pv = Vec::new();
+            let score = context.negamax(&mut board, depth, 0, -INFINITY, INFINITY, &mut pv);
+            // a search which ran out of time didn't look at every move, so it can't be trusted
+            if context.stopped || pv.is_empty() {
+                break;
+            }
+            result.best_move = pv.first().cloned();
+            result.score = score;
+            result.depth = depth;
+            result.principal_variation = pv.clone();
+            context.previous_pv = pv;
+            // there's no need to look any deeper once a forced mate has been found
+            if score.abs() >= MATE_THRESHOLD {
+                break;
+            }
+        }
+        result.nodes = context.nodes;
+        result
+    }
+}
+
+
+/// Everything that changes over the course of a single search
+struct SearchContext<'a> {
+    start: Instant,
+    limits: SearchLimits,
+    nodes: u64,
+    stopped: bool,  // set once time runs out, after which all the scores are meaningless
+    previous_pv: Vec<ChessMove>,  // the best line from the last depth, which is searched first
+    table: &'a mut TranspositionTable,
+    evaluator: &'a dyn Evaluator
+}
+
+impl SearchContext<'_> {
+    /// Score the position for the side to move by searching the given number of plies ahead, filling in the best line found
+    fn negamax(self: &mut Self, board: &mut ChessBoard, depth: usize, ply: usize, mut alpha: i64, mut beta: i64, pv: &mut Vec<ChessMove>) -> i64 {
+        pv.clear();
+        self.nodes += 1;
+        if self.limits.time_limit.is_some_and(|limit| self.start.elapsed() >= limit) {
+            self.stopped = true;
+            return 0;
+        }
+
+        // going back to a position already seen is a draw, since the same moves could just be played again
+        if ply > 0 && (board.get_repetition_count() >= 2 || board.get_draw_reason().is_some()) {
+            return 0;
+        }
+        // quiescence search finds checkmates on its own, so there's no need to generate every move here
+        if depth == 0 {
+            return quiescence(board, alpha, beta, ply, self.evaluator, &mut self.nodes);
+        }
+        let turn = board.state.current_turn;
+        let moves = board.get_all_moves(turn);
+        if moves.is_empty() {
+            return match board.is_checked(turn) {
+                true => -(MATE_SCORE - ply as i64),
+                false => 0
+            };
+        }
+
+        // don't bother looking for mates which are longer than one already found
+        alpha = alpha.max(-(MATE_SCORE - ply as i64));
+        beta = beta.min(MATE_SCORE - ply as i64 - 1);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        // the position may have been searched already through a different move order, or at a lower depth on the last iteration
+        let key = board.zobrist_key();
+        let mut table_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            table_move = entry.best_move.clone();
+            // the root always needs searching so there's a best move and principal variation
+            if ply > 0 && entry.depth >= depth {
+                let score = score_from_table(entry.score, ply);
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha
+                };
+                if usable {
+                    return score;
+                }
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+        for the_move in order_moves(board, moves, table_move.as_ref().or(self.previous_pv.get(ply))) {
+            let undo = board.make_move(&the_move).expect("Searched a generated move which couldn't be performed");
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            board.unmake_move(undo);
+            if self.stopped {
+                return 0;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(the_move.clone());
+                pv.clear();
+                pv.push(the_move);
+                pv.append(&mut child_pv);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        let bound = match alpha {
+            _ if alpha >= beta => Bound::Lower,
+            _ if alpha > original_alpha => Bound::Exact,
+            _ => Bound::Upper
+        };
+        self.table.store(key, depth, bound, score_to_table(alpha, ply), best_move);
+        alpha
+    }
+}
+
+
+/// Mate scores count plies from the root, but the same position can be reached at different plies, so they're stored counting from the position
+fn score_to_table(score: i64, ply: usize) -> i64 {
+    match score {
+        s if s >= MATE_THRESHOLD => s + ply as i64,
+        s if s <= -MATE_THRESHOLD => s - ply as i64,
+        s => s
+    }
+}
+
+/// Turn a score from the transposition table back into one counting plies from the root
+fn score_from_table(score: i64, ply: usize) -> i64 {
+    match score {
+        s if s >= MATE_THRESHOLD => s - ply as i64,
+        s if s <= -MATE_THRESHOLD => s + ply as i64,
+        s => s
+    }
+}
+
+
+/// Search only the captures and promotions until the position is quiet before evaluating it, so a search which stops right after a capture
+/// doesn't miss the recapture. While in check every move is searched since there's no choice but to get out of it.
+/// The score is for the side to move, using the evaluator for positions where there's nothing left to capture.
+/// https://www.chessprogramming.org/Quiescence_Search
+pub fn quiescence(board: &mut ChessBoard, mut alpha: i64, beta: i64, ply: usize, evaluator: &dyn Evaluator, nodes: &mut u64) -> i64 {
+    *nodes += 1;
+    let turn = board.state.current_turn;
+    let in_check = board.is_checked(turn) && ply < MAX_QUIESCENCE_PLY;
+    let moves = match in_check {
+        true => {
+            let evasions = board.get_all_moves(turn);
+            if evasions.is_empty() {
+                return -(MATE_SCORE - ply as i64);
+            }
+            evasions
+        },
+        false => {
+            // the side to move doesn't have to capture, so it can at least get the current evaluation
+            let stand_pat = evaluator.evaluate(board, turn);
+            if stand_pat >= beta || ply >= MAX_QUIESCENCE_PLY {
+                return stand_pat.min(beta);
+            }
+            alpha = alpha.max(stand_pat);
+            // captures which lose material can't be better than standing pat
+            board.get_filtered_moves(turn, MoveFilter::CapturesAndPromotions).into_iter()
+                .filter(|m| m.captures.is_none() || board.static_exchange_eval(m) >= 0)
+                .collect()
+        }
+    };
+
+    for the_move in order_moves(board, moves, None) {
+        let undo = board.make_move(&the_move).expect("Searched a generated move which couldn't be performed");
+        let score = -quiescence(board, -beta, -alpha, ply + 1, evaluator, nodes);
+        board.unmake_move(undo);
+        if score > alpha {
+            alpha = score;
+            if alpha >= beta {
+                break;
+            }
+        }
+    }
+    alpha
+}
+
+
+/// Put the moves most likely to be best first, since alpha-beta can skip more moves the sooner it finds a good one.
+/// That's the move from the last principal variation, then captures of the most valuable pieces by the least valuable ones.
+fn order_moves(board: &ChessBoard, moves: Vec<ChessMove>, pv_move: Option<&ChessMove>) -> Vec<ChessMove> {
+    moves.into_iter()
+        .sorted_by_cached_key(|m| {
+            if pv_move == Some(m) {
+                return i64::MIN;
+            }
+            let capture_value = m.captures
+                .and_then(|position| board.get_square_by_position(position))
+                .map(|captured| captured.get_material() as i64 * 10 - board.get_square_by_position(m.from_square).map_or(0, |p| p.get_material() as i64))
+                .unwrap_or(0);
+            let promotion_value = m.promotion_piece().map_or(0, |p| p.get_material() as i64 * 10);
+            -(capture_value + promotion_value)
+        })
+        .collect()
+}
+
+
+/// Write out a line of moves in SAN, e.g. `Qxf7+ Kd8 Qf8#`
+fn san_line(board_state: &ChessBoard, moves: &[ChessMove]) -> String {
+    let mut board = board_state.clone_without_history();
+    moves.iter()
+        .map(|m| {
+            let san = m.to_san(&board);
+            board.perform_move(m).expect("Principal variation has a move which couldn't be performed");
+            san
+        })
+        .join(" ")
+}
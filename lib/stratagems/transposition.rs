@@ -0,0 +1,141 @@
+use core::fmt;
+
+use crate::gamelogic::ChessMove;
+
+
+/// How a stored score relates to the real score of the position, since alpha-beta often stops before finding the exact score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,  // the search failed high, so the real score is at least this
+    Upper,  // the search failed low, so the real score is at most this
+}
+
+/// What a search found out about a position
+#[derive(Debug, Clone)]
+pub struct TableEntry {
+    pub key: u64,
+    pub depth: usize,  // how many plies were searched below the position
+    pub bound: Bound,
+    pub score: i64,
+    pub best_move: Option<ChessMove>,
+    age: u8  // which search stored the entry, so entries from earlier moves of the game can be replaced first
+}
+
+/// Counts of how well the table is working, to help pick its size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    pub used: usize,  // slots with an entry in them
+    pub capacity: usize
+}
+
+impl TableStats {
+    /// The fraction of probes which found an entry for the position
+    pub fn hit_rate(self: &Self) -> f64 {
+        match self.probes {
+            0 => 0.0,
+            probes => self.hits as f64 / probes as f64
+        }
+    }
+
+    /// The fraction of slots which have an entry in them
+    pub fn fill_rate(self: &Self) -> f64 {
+        match self.capacity {
+            0 => 0.0,
+            capacity => self.used as f64 / capacity as f64
+        }
+    }
+}
+
+impl fmt::Display for TableStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits from {} probes ({:.1}%), {} stores, {:.1}% full", self.hits, self.probes, self.hit_rate() * 100.0, self.stores, self.fill_rate() * 100.0)
+    }
+}
+
+
+/// A fixed size table of search results keyed by Zobrist hash, so a position reached by different move orders is only searched once
+/// and the best move from earlier iterations is tried first. Each hash maps to one slot, and when two positions want the same slot
+/// the deeper or newer result is kept.
+/// https://www.chessprogramming.org/Transposition_Table
+pub struct TranspositionTable {
+    entries: Vec<Option<TableEntry>>,
+    age: u8,
+    stats: TableStats
+}
+
+impl TranspositionTable {
+    /// Create a table using roughly the given number of megabytes, which always has room for at least one entry
+    pub fn new(megabytes: usize) -> Self {
+        let capacity = (megabytes * 1024 * 1024 / std::mem::size_of::<Option<TableEntry>>()).max(1);
+        TranspositionTable {
+            entries: vec![None; capacity],
+            age: 0,
+            stats: TableStats { capacity, ..Default::default() }
+        }
+    }
+
+    pub fn capacity(self: &Self) -> usize {
+        self.entries.len()
+    }
+
+    /// Look up what's known about the position with the given hash
+    pub fn probe(self: &mut Self, key: u64) -> Option<&TableEntry> {
+        self.stats.probes += 1;
+        let index = self.get_index(key);
+        match &self.entries[index] {
+            Some(entry) if entry.key == key => {
+                self.stats.hits += 1;
+                Some(entry)
+            },
+            _ => None
+        }
+    }
+
+    /// Save a search result, unless the slot has a deeper result from the current search for a different position
+    pub fn store(self: &mut Self, key: u64, depth: usize, bound: Bound, score: i64, best_move: Option<ChessMove>) {
+        let index = self.get_index(key);
+        let replace = match &self.entries[index] {
+            Some(existing) => existing.key == key || existing.age != self.age || depth >= existing.depth,
+            None => {
+                self.stats.used += 1;
+                true
+            }
+        };
+        if !replace {
+            return;
+        }
+        // a result which failed low may not know the best move, so keep the one found before
+        let best_move = best_move.or_else(|| match &self.entries[index] {
+            Some(existing) if existing.key == key => existing.best_move.clone(),
+            _ => None
+        });
+        self.entries[index] = Some(TableEntry { key, depth, bound, score, best_move, age: self.age });
+        self.stats.stores += 1;
+    }
+
+    /// Start the search for a new move of the game. Entries from earlier searches are kept but are the first to be replaced.
+    pub fn new_search(self: &mut Self) {
+        self.age = self.age.wrapping_add(1);
+        self.stats = TableStats { used: self.stats.used, capacity: self.stats.capacity, ..Default::default() };
+    }
+
+    /// Remove every entry, e.g. when starting a new game
+    pub fn clear(self: &mut Self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.age = 0;
+        self.stats = TableStats { capacity: self.stats.capacity, ..Default::default() };
+    }
+
+    /// How well the table has worked since the current search started
+    pub fn get_stats(self: &Self) -> TableStats {
+        self.stats
+    }
+
+    fn get_index(self: &Self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+}
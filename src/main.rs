@@ -1,31 +1,76 @@
-use chessbot_lib::{stratagems::{random_aggro::RandomAggro, cole_miner::ColeMiner}, runners::{Runner, local_game::LocalGame, chess_com::ChessComGame}};
+use chessbot_lib::{gamelogic::{board::ChessBoard, image::BoardImage, pgn::parse_pgn, Side}, stratagems::{random_aggro::RandomAggro, cole_miner::ColeMiner, searcher::Searcher}, runners::{Runner, local_game::LocalGame, chess_com::ChessComGame}};
 
 extern crate chessbot_lib;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 
 
 /// Semi-modular ChessBot for a ChessBot Tournament.
 #[derive(Debug, Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Bot strategy mode. Determines how the Bot's moves are chosen for a given board state.
+    /// Searcher looks the furthest ahead and is the one to use for tournament entries.
     #[arg(value_enum, required=true)]
-    strategem: StrategemChoices,
+    strategem: Option<StrategemChoices>,
 
     /// Choice for how to interface the chess bot w/ a chess game. The runner handles reading state and giving the bot's inputs to the game.
     #[arg(value_enum, required=true)]
-    runner: RunnerChoices,
+    runner: Option<RunnerChoices>,
 
     /// Arbitrary additional arguments as required by the different runners.
+    /// LocalGame takes an optional path to save the game's PGN to, and ChessCom takes the PHPSESSID followed by the optional PGN path.
     #[arg(required=false)]
     runner_args: Vec<String>
 }
 
 
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Count the move sequences of the given depth from a position, to verify move generation.
+    Perft {
+        /// Forsyth-Edwards string of the position to start from.
+        fen: String,
+
+        /// How many moves deep to count.
+        depth: usize,
+
+        /// Use Chess960 rules, which allows Shredder-FEN and X-FEN castling rights.
+        #[arg(long)]
+        chess960: bool
+    },
+    /// Draw a position as an SVG or PNG image, or every position of a game.
+    Render {
+        /// Forsyth-Edwards string of the position to draw.
+        #[arg(required_unless_present = "pgn")]
+        fen: Option<String>,
+
+        /// Draw every position of the first game in this PGN file instead, adding the move number to each output file name.
+        #[arg(long, conflicts_with = "fen", requires = "output")]
+        pgn: Option<String>,
+
+        /// File to write the image to, which is a PNG if it ends with '.png' and an SVG otherwise. Without it the SVG is printed.
+        #[arg(long, short)]
+        output: Option<String>,
+
+        /// Draw the board with Black at the bottom.
+        #[arg(long)]
+        flip: bool,
+
+        /// Use Chess960 rules, which allows Shredder-FEN and X-FEN castling rights.
+        #[arg(long)]
+        chess960: bool
+    }
+}
+
+
 #[derive(Debug, ValueEnum, Clone)]
 #[value(rename_all="PascalCase")]
 enum StrategemChoices {
+    Searcher,
     RandomAggro,
     ColeMiner
 }
@@ -43,13 +88,24 @@ fn main() {
     let args = Args::parse();
     // eprintln!("{:#?}", args);
 
+    match args.command {
+        Some(Commands::Perft { fen, depth, chess960 }) => return run_perft(fen, depth, chess960),
+        Some(Commands::Render { fen, pgn, output, flip, chess960 }) => return run_render(fen, pgn, output, flip, chess960),
+        None => ()
+    }
+    // clap makes sure these are set if there's no subcommand
+    let strategem = args.strategem.unwrap();
+    let runner = args.runner.unwrap();
+
     // Given there's not a way to dynamically handle the type as a variable, instead we'll just handle each possible supported variation of runner+strategem combination.
-    let mut game_runner: Box<dyn Runner> = match args.runner {
-        RunnerChoices::LocalGame => match args.strategem {
+    let mut game_runner: Box<dyn Runner> = match runner {
+        RunnerChoices::LocalGame => match strategem {
+            StrategemChoices::Searcher => Box::new(LocalGame::initialize::<Searcher>(args.runner_args).unwrap()),
             StrategemChoices::RandomAggro => Box::new(LocalGame::initialize::<RandomAggro>(args.runner_args).unwrap()),
             StrategemChoices::ColeMiner => Box::new(LocalGame::initialize::<ColeMiner>(args.runner_args).unwrap()),
         }
-        RunnerChoices::ChessCom => match args.strategem {
+        RunnerChoices::ChessCom => match strategem {
+            StrategemChoices::Searcher => Box::new(ChessComGame::initialize::<Searcher>(args.runner_args).unwrap()),
             StrategemChoices::RandomAggro => Box::new(ChessComGame::initialize::<RandomAggro>(args.runner_args).unwrap()),
             StrategemChoices::ColeMiner => Box::new(ChessComGame::initialize::<ColeMiner>(args.runner_args).unwrap()),
         }
@@ -59,5 +115,103 @@ fn main() {
     println!("{}", "=".to_string().repeat(80));
     println!("{:?}", victory);
     println!("{}", "=".to_string().repeat(80));
+}
+
 
+/// Parse the FEN string of a subcommand, exiting if it's invalid
+fn parse_fen_or_exit(fen: String, chess960: bool) -> ChessBoard {
+    let parsed_board = match chess960 {
+        true => ChessBoard::from_forsyth_edwards_960_strict(fen),
+        false => ChessBoard::from_forsyth_edwards_strict(fen)
+    };
+    match parsed_board {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Invalid FEN string: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+
+/// Print the perft node count after each legal move and then the total
+fn run_perft(fen: String, depth: usize, chess960: bool) {
+    let board = parse_fen_or_exit(fen, chess960);
+    let start = std::time::Instant::now();
+    let divided = board.perft_divide(depth);
+    for (the_move, nodes) in divided.iter() {
+        println!("{}: {}", the_move.to_uci(), nodes);
+    }
+    let total: u64 = match depth {
+        0 => 1,
+        _ => divided.iter().map(|(_, nodes)| nodes).sum()
+    };
+    println!("\nNodes searched: {} ({:.2?})", total, start.elapsed());
+}
+
+
+/// Draw the position, or each position of a game, as an image
+fn run_render(fen: Option<String>, pgn_path: Option<String>, output: Option<String>, flip: bool, chess960: bool) {
+    let orientation = match flip {
+        true => Side::Black,
+        false => Side::White
+    };
+    let boards = match pgn_path {
+        Some(path) => {
+            let pgn = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                eprintln!("Unable to read PGN file '{}': {}", path, e);
+                std::process::exit(1);
+            });
+            let game = match parse_pgn(&pgn).map(|games| games.into_iter().next()) {
+                Ok(Some(g)) => g,
+                Ok(None) => {
+                    eprintln!("There are no games in the PGN file '{}'", path);
+                    std::process::exit(1);
+                },
+                Err(e) => {
+                    eprintln!("Invalid PGN file '{}': {}", path, e);
+                    std::process::exit(1);
+                }
+            };
+            game.replay_positions().unwrap_or_else(|e| {
+                eprintln!("Unable to replay the game: {}", e);
+                std::process::exit(1);
+            })
+        },
+        None => vec![parse_fen_or_exit(fen.unwrap(), chess960)]  // clap makes sure there's a FEN if there's no PGN
+    };
+
+    let is_game = boards.len() > 1;
+    for (ply, board) in boards.iter().enumerate() {
+        let image = BoardImage::new(board).orientation(orientation).last_move().check();
+        let path = match (&output, is_game) {
+            (None, _) => {
+                print!("{}", image.to_svg());
+                continue;
+            },
+            // number each position of a game, e.g. game.png becomes game-000.png, game-001.png, ...
+            (Some(path), true) => {
+                let path = std::path::Path::new(path);
+                let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                let file_name = match path.extension() {
+                    Some(extension) => format!("{}-{:03}.{}", stem, ply, extension.to_string_lossy()),
+                    None => format!("{}-{:03}", stem, ply)
+                };
+                path.with_file_name(file_name)
+            },
+            (Some(path), false) => std::path::PathBuf::from(path)
+        };
+        let contents = match path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+            true => image.to_png().unwrap_or_else(|e| {
+                eprintln!("Unable to draw the board: {}", e);
+                std::process::exit(1);
+            }),
+            false => image.to_svg().into_bytes()
+        };
+        if let Err(e) = std::fs::write(&path, contents) {
+            eprintln!("Unable to write image file '{}': {}", path.display(), e);
+            std::process::exit(1);
+        }
+        println!("Wrote {}", path.display());
+    }
 }
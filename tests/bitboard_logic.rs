@@ -0,0 +1,45 @@
+use chessbot_lib::gamelogic::{bitboard, board::ChessBoard, pieces::PieceType, name_to_index_pair, Side};
+
+#[test]
+fn starting_position_bitboards() {
+    let board = ChessBoard::new();
+    assert_eq!(board.get_piece_bitboard(Side::White, PieceType::Pawn), 0x0000_0000_0000_FF00);
+    assert_eq!(board.get_piece_bitboard(Side::Black, PieceType::Pawn), 0x00FF_0000_0000_0000);
+    assert_eq!(board.get_side_occupancy(Side::White), 0x0000_0000_0000_FFFF);
+    assert_eq!(board.get_side_occupancy(Side::Black), 0xFFFF_0000_0000_0000);
+    assert_eq!(board.get_piece_bitboard(Side::White, PieceType::King), bitboard::square_mask((4, 0)));
+}
+
+#[test]
+fn bitboards_follow_moves() {
+    let mut board = ChessBoard::new();
+    let knight = board.get_square_by_name("g1".to_string()).unwrap().unwrap();
+    let knight_move = knight.get_specific_move(&board, name_to_index_pair("f3".to_string()).unwrap()).unwrap();
+    assert!(board.perform_move(&knight_move).is_ok());
+    let knights = board.get_piece_bitboard(Side::White, PieceType::Knight);
+    assert_eq!(knights, bitboard::square_mask((1, 0)) | bitboard::square_mask((5, 2)));
+    assert_eq!(board.get_occupancy().count_ones(), 32);
+}
+
+#[test]
+fn rook_attacks_stop_at_blockers() {
+    // rook on d4 blocked on d6 and f4
+    let occupancy = bitboard::square_mask((3, 5)) | bitboard::square_mask((5, 3));
+    let attacks = bitboard::rook_attacks((3, 3), occupancy);
+    let attacked = bitboard::positions(attacks).collect::<Vec<(usize, usize)>>();
+    assert!(attacked.contains(&(3, 5)));
+    assert!(!attacked.contains(&(3, 6)));
+    assert!(attacked.contains(&(5, 3)));
+    assert!(!attacked.contains(&(6, 3)));
+    assert_eq!(attacks.count_ones(), 10);
+}
+
+#[test]
+fn attackers_of_square() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/3r4/8/1N3B2/8/3RK3 w - - 0 1".to_string()).unwrap();
+    // d4 is threatened by the rooks on d1 and d5, the knight on b3, and not the bishop on f3
+    let white_attackers = board.get_square_threats(Side::White, (3, 3));
+    assert_eq!(white_attackers.len(), 2);
+    assert!(board.is_square_threatened(Side::Black, (3, 3)));
+    assert!(!board.is_checked(Side::White));
+}
@@ -0,0 +1,65 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, builder::ChessBoardBuilder, pieces::PieceType, ChessError, Side};
+
+#[test]
+fn builder_matches_fen() {
+    let board = ChessBoardBuilder::new()
+        .place("e1", Side::White, PieceType::King)
+        .place("h1", Side::White, PieceType::Rook)
+        .place("e5", Side::White, PieceType::Pawn)
+        .place("e8", Side::Black, PieceType::King)
+        .place("d5", Side::Black, PieceType::Pawn)
+        .castling(Side::White, true, true)
+        .en_passant(Some("d6"))
+        .clocks(0, 12)
+        .build()
+        .unwrap();
+    assert_eq!(board.to_forsyth_edwards(), "4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 12");
+    assert!(board.validate().is_empty());
+}
+
+#[test]
+fn builder_edits_existing_board() {
+    let board = ChessBoardBuilder::from_board(&ChessBoard::new())
+        .remove("d1")
+        .place("d4", Side::White, PieceType::Queen)
+        .castling(Side::Black, false, false)
+        .turn(Side::Black)
+        .build()
+        .unwrap();
+    assert_eq!(board.to_forsyth_edwards(), "rnbqkbnr/pppppppp/8/8/3Q4/8/PPPPPPPP/RNB1KBNR b KQk - 0 1");
+}
+
+#[test]
+fn builder_reports_invalid_square() {
+    let board = ChessBoardBuilder::new()
+        .place("i9", Side::White, PieceType::King)
+        .build();
+    assert!(matches!(board, Err(ChessError::InvalidArgument(_))));
+}
+
+#[test]
+fn diagram_matches_fen() {
+    let board = ChessBoard::from_diagram("
+        8 r···k··r
+        7 ········
+        6 ········
+        5 ···pP···
+        4 ········
+        3 ········
+        2 ········
+        1 ····K··R
+          abcdefgh
+    ").unwrap();
+    assert_eq!(board.to_forsyth_edwards(), "r3k2r/8/8/3pP3/8/8/8/4K2R w - - 0 1");
+    // plain dots work too, and the column letters are optional
+    let dotted = ChessBoard::from_diagram("8 r...k..r\n7 ........\n6 ........\n5 ...pP...\n4 ........\n3 ........\n2 ........\n1 ....K..R").unwrap();
+    assert_eq!(dotted.to_forsyth_edwards(), board.to_forsyth_edwards());
+}
+
+#[test]
+fn diagram_rejects_bad_rows() {
+    assert!(ChessBoard::from_diagram("8 ········").is_err());
+    assert!(ChessBoard::from_diagram("8 ·······\n7 ········\n6 ········\n5 ········\n4 ········\n3 ········\n2 ········\n1 ········").is_err());
+    assert!(ChessBoard::from_diagram("9 ········\n7 ········\n6 ········\n5 ········\n4 ········\n3 ········\n2 ········\n1 ········").is_err());
+    assert!(ChessBoard::from_diagram("8 ·······x\n7 ········\n6 ········\n5 ········\n4 ········\n3 ········\n2 ········\n1 ········").is_err());
+}
@@ -0,0 +1,52 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, image::BoardImage, pgn::parse_pgn, render::Highlight, Side};
+
+#[test]
+fn svg_has_every_square_and_piece() {
+    let board = ChessBoard::new();
+    let svg = BoardImage::new(&board).coordinates(false).to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"512\" height=\"512\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<rect").count(), 64);
+    assert_eq!(svg.matches("<g transform").count(), 32);
+    assert_eq!(svg.matches("<text").count(), 0);
+}
+
+#[test]
+fn svg_overlays_and_flip() {
+    let board = ChessBoard::new();
+    let svg = BoardImage::new(&board)
+        .overlay([(4, 1)], Highlight::LastMove)
+        .arrow((4, 1), (4, 3))
+        .to_svg();
+    assert_eq!(svg.matches("<rect").count(), 65);
+    assert_eq!(svg.matches("<polygon").count(), 1);
+    assert_eq!(svg.matches("<text").count(), 16);
+    // a1 is in the bottom left for White, and the top right for Black
+    let white_svg = BoardImage::new(&board).to_svg();
+    let black_svg = BoardImage::new(&board).orientation(Side::Black).to_svg();
+    assert!(white_svg.contains("<rect x=\"0\" y=\"315\" width=\"45\" height=\"45\" fill=\"#b58863\"/>"));
+    assert!(black_svg.contains("<rect x=\"315\" y=\"0\" width=\"45\" height=\"45\" fill=\"#b58863\"/>"));
+}
+
+#[test]
+fn png_has_the_right_size() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 w - - 0 1".to_string()).unwrap();
+    let png = BoardImage::new(&board).square_size(10).to_png().unwrap();
+    assert_eq!(&png[0..8], b"\x89PNG\r\n\x1a\n");
+    // the width and height are the first two fields of the IHDR chunk
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 80);
+    assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 80);
+    assert!(BoardImage::new(&board).square_size(0).to_png().is_err());
+}
+
+#[test]
+fn replay_positions_for_each_move() {
+    let game = parse_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0").unwrap().remove(0);
+    let positions = game.replay_positions().unwrap();
+    assert_eq!(positions.len(), 8);
+    assert_eq!(positions[0].to_forsyth_edwards(), ChessBoard::new().to_forsyth_edwards());
+    assert_eq!(positions[7].move_list.len(), 7);
+    // the final position has the last move and the checked King highlighted
+    let svg = BoardImage::new(&positions[7]).last_move().check().to_svg();
+    assert_eq!(svg.matches("fill-opacity").count(), 3);
+}
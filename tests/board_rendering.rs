@@ -0,0 +1,49 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, render::BoardRenderer, Side};
+
+#[test]
+fn ascii_render_from_white() {
+    let board = ChessBoard::new();
+    let rendered = BoardRenderer::new(&board).ascii(true).colored(false).to_string();
+    let expected = "8 r n b q k b n r \n\
+                    7 p p p p p p p p \n\
+                    6 . . . . . . . . \n\
+                    5 . . . . . . . . \n\
+                    4 . . . . . . . . \n\
+                    3 . . . . . . . . \n\
+                    2 P P P P P P P P \n\
+                    1 R N B Q K B N R \n  \
+                      a b c d e f g h\n";
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn ascii_render_from_black() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 w - - 0 1".to_string()).unwrap();
+    let rendered = BoardRenderer::new(&board).ascii(true).colored(false).orientation(Side::Black).to_string();
+    let expected = "1 . . . K . . . R \n\
+                    2 . . . . . . . . \n\
+                    3 . . . . . . . . \n\
+                    4 . . . . . . . . \n\
+                    5 . . . . . . . . \n\
+                    6 . . . . . . . . \n\
+                    7 . . . . . . . . \n\
+                    8 . . . k . . . . \n  \
+                      h g f e d c b a\n";
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn overlays_change_the_rendering() {
+    colored::control::set_override(true);
+    let mut board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 w - - 0 1".to_string()).unwrap();
+    let rook_move = board.parse_san("Ra8+").unwrap();
+    board.make_move(&rook_move).unwrap();
+    let plain = BoardRenderer::new(&board).to_string();
+    let highlighted = BoardRenderer::new(&board).last_move().check().to_string();
+    assert_ne!(plain, highlighted);
+    // the pieces are the same once the colours are taken out
+    assert_eq!(
+        BoardRenderer::new(&board).colored(false).to_string(),
+        BoardRenderer::new(&board).last_move().check().colored(false).to_string()
+    );
+}
@@ -103,4 +103,14 @@ fn get_square_a9_err() {
 fn get_square_abc123_err() {
     let board = ChessBoard::new();
     assert!(board.get_square_by_name("abc123".to_string()).is_err());
-}
\ No newline at end of file
+}
+
+#[test]
+fn get_squares_matches_get_square_by_index() {
+    let board = ChessBoard::new();
+    for (col, column) in board.get_squares().iter().enumerate() {
+        for (row, square) in column.iter().enumerate() {
+            assert_eq!(square.map(|p| (p.side, p.piece_type)), board.get_square_by_index(col, row).map(|p| (p.side, p.piece_type)));
+        }
+    }
+}
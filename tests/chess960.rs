@@ -0,0 +1,107 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pgn::{parse_pgn, PgnGame}, pieces::PieceType, MoveType, Side, Variant};
+
+fn board_960(fen: &str) -> ChessBoard {
+    ChessBoard::from_forsyth_edwards_960(fen.to_string()).unwrap()
+}
+
+#[test]
+fn start_positions_by_index() {
+    assert_eq!(ChessBoard::new_chess960(518).unwrap().to_forsyth_edwards(), ChessBoard::new().to_forsyth_edwards());
+    assert_eq!(ChessBoard::new_chess960(0).unwrap().to_forsyth_edwards(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    assert_eq!(ChessBoard::new_chess960(959).unwrap().to_forsyth_edwards(), "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1");
+    assert!(ChessBoard::new_chess960(960).is_err());
+}
+
+#[test]
+fn every_start_position_is_different_and_valid() {
+    let mut seen = std::collections::HashSet::new();
+    for index in 0..960 {
+        let board = ChessBoard::new_chess960(index).unwrap();
+        assert_eq!(board.variant, Variant::Chess960);
+        assert!(seen.insert(board.to_forsyth_edwards()));
+        let back_row = (0..8).map(|col| board.get_square_by_index(col, 0).unwrap().piece_type).collect::<Vec<PieceType>>();
+        let king_col = back_row.iter().position(|p| *p == PieceType::King).unwrap();
+        assert_eq!(board.get_castling_rook_column(Side::White, false), back_row.iter().position(|p| *p == PieceType::Rook).unwrap());
+        assert!(board.get_castling_rook_column(Side::White, false) < king_col && king_col < board.get_castling_rook_column(Side::White, true));
+        let bishop_cols = (0..8).filter(|col| back_row[*col] == PieceType::Bishop).collect::<Vec<usize>>();
+        assert_ne!(bishop_cols[0] % 2, bishop_cols[1] % 2);
+    }
+}
+
+#[test]
+fn shredder_and_x_fen_castling_rights() {
+    let shredder = board_960("1r2k1r1/8/8/8/8/8/8/RR2K2R w HBgb - 0 1");
+    assert_eq!(shredder.get_castling_rook_column(Side::White, true), 7);
+    assert_eq!(shredder.get_castling_rook_column(Side::White, false), 1);
+    assert_eq!(shredder.get_castling_rook_column(Side::Black, true), 6);
+    assert_eq!(shredder.get_castling_rook_column(Side::Black, false), 1);
+    // X-FEN only uses the file when the rook isn't the outermost one
+    assert_eq!(shredder.to_forsyth_edwards(), "1r2k1r1/8/8/8/8/8/8/RR2K2R w KBkq - 0 1");
+    let x_fen = board_960(&shredder.to_forsyth_edwards());
+    assert_eq!(x_fen.get_castling_rook_column(Side::White, false), 1);
+    assert_eq!(x_fen.to_forsyth_edwards(), shredder.to_forsyth_edwards());
+
+    assert!(ChessBoard::from_forsyth_edwards("1r2k1r1/8/8/8/8/8/8/RR2K2R w HBgb - 0 1".to_string()).is_err());
+    assert!(ChessBoard::from_forsyth_edwards_960("4k3/8/8/8/8/8/8/4K3 w K - 0 1".to_string()).is_err());
+}
+
+#[test]
+fn castling_onto_the_kings_square() {
+    // the king is already on g1, so only the rook moves when castling kingside
+    let mut board = board_960("4k3/8/8/8/8/8/8/1R4KR w HB - 0 1");
+    let castle = board.parse_san("O-O").unwrap();
+    assert_eq!(castle.move_type, MoveType::Castle);
+    assert_eq!(castle.to_uci(), "g1h1");
+    let undo = board.make_move(&castle).unwrap();
+    assert_eq!(board.to_forsyth_edwards(), "4k3/8/8/8/8/8/8/1R3RK1 b - - 1 1");
+    board.unmake_move(undo);
+    assert_eq!(board.to_forsyth_edwards(), "4k3/8/8/8/8/8/8/1R4KR w KQ - 0 1");
+
+    // queenside castling moves the king onto where the rook was, and the rook over where the king was
+    let castle = board.parse_san("O-O-O").unwrap();
+    assert_eq!(castle.to_uci(), "g1b1");
+    let undo = board.make_move(&castle).unwrap();
+    assert_eq!(board.to_forsyth_edwards(), "4k3/8/8/8/8/8/8/2KR3R b - - 1 1");
+    assert_eq!(board.zobrist_key(), board.compute_zobrist_key());
+    board.unmake_move(undo);
+    assert_eq!(board.to_forsyth_edwards(), "4k3/8/8/8/8/8/8/1R4KR w KQ - 0 1");
+}
+
+#[test]
+fn castling_needs_a_clear_and_safe_path() {
+    // the knight on b1 is in the way of the rook going to d1
+    let board = board_960("4k3/8/8/8/8/8/8/RNK4R w HA - 0 1");
+    assert!(board.parse_san("O-O-O").is_err());
+    assert!(board.parse_san("O-O").is_ok());
+    // the king would pass over the attacked e1 square
+    let board = board_960("4r1k1/8/8/8/8/8/8/RK5R w HA - 0 1");
+    assert!(board.parse_san("O-O").is_err());
+    assert!(board.parse_san("O-O-O").is_ok());
+}
+
+#[test]
+fn perft_chess960_positions() {
+    // from https://www.chessprogramming.org/Chess960_Perft_Results
+    let board = board_960("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+    assert_eq!(board.perft(1), 21);
+    assert_eq!(board.perft(2), 528);
+    assert_eq!(board.perft(3), 12189);
+    let board = board_960("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9");
+    assert_eq!(board.perft(1), 21);
+    assert_eq!(board.perft(2), 807);
+    assert_eq!(board.perft(3), 18002);
+}
+
+#[test]
+fn pgn_keeps_the_variant() {
+    let mut board = board_960("4k3/8/8/8/8/8/8/1R4KR w HB - 0 1");
+    for san in ["O-O-O", "Kf7", "Rh7+"] {
+        let the_move = board.parse_san(san).unwrap();
+        board.perform_move_and_record(&the_move).unwrap();
+    }
+    let game = PgnGame::from_board(&board, None);
+    assert_eq!(game.get_tag("Variant"), Some("Chess960"));
+    let parsed = parse_pgn(&game.to_pgn().unwrap()).unwrap().remove(0).replay().unwrap();
+    assert_eq!(parsed.variant, Variant::Chess960);
+    assert_eq!(parsed.to_forsyth_edwards(), board.to_forsyth_edwards());
+}
@@ -0,0 +1,131 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pieces::PieceType, Side};
+use chessbot_lib::stratagems::evaluation::{
+    Evaluator, KingSafetyEvaluator, MaterialEvaluator, MobilityEvaluator, PawnStructureEvaluator, PieceSquareEvaluator, TaperedPieceSquareEvaluator,
+    WeightedEvaluator, MAX_PHASE
+};
+use chessbot_lib::stratagems::searcher::{SearchLimits, Searcher};
+
+
+fn board(fen: &str) -> ChessBoard {
+    ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap()
+}
+
+/// Every evaluator should score the starting position as even, and give each side the negative of the other's score
+fn assert_symmetric(evaluator: &dyn Evaluator) {
+    assert_eq!(evaluator.evaluate(&ChessBoard::new(), Side::White), 0);
+    let position = board("r1bqk2r/pp2bppp/2n1pn2/3p4/3P4/2NBPN2/PP3PPP/R1BQK2R w KQkq - 0 8");
+    assert_eq!(evaluator.evaluate(&position, Side::White), -evaluator.evaluate(&position, Side::Black));
+}
+
+
+#[test]
+fn evaluators_are_symmetric() {
+    assert_symmetric(&MaterialEvaluator);
+    assert_symmetric(&PieceSquareEvaluator);
+    assert_symmetric(&MobilityEvaluator);
+    assert_symmetric(&KingSafetyEvaluator);
+    assert_symmetric(&PawnStructureEvaluator);
+    assert_symmetric(&TaperedPieceSquareEvaluator::default());
+    assert_symmetric(&WeightedEvaluator::standard());
+}
+
+
+#[test]
+fn material_and_piece_squares() {
+    // White is a Knight up
+    let position = board("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+    assert_eq!(MaterialEvaluator.evaluate(&position, Side::White), 300);
+    assert_eq!(MaterialEvaluator.evaluate(&position, Side::Black), -300);
+    // Knights are better in the center than in the corner, for both sides
+    assert!(PieceSquareEvaluator::get_square_bonus(Side::White, PieceType::Knight, (3, 3)) > PieceSquareEvaluator::get_square_bonus(Side::White, PieceType::Knight, (0, 0)));
+    assert_eq!(
+        PieceSquareEvaluator::get_square_bonus(Side::White, PieceType::Pawn, (4, 6)),
+        PieceSquareEvaluator::get_square_bonus(Side::Black, PieceType::Pawn, (4, 1))
+    );
+    // the plain tables are the middlegame half of the tapered ones
+    assert_eq!(
+        PieceSquareEvaluator::get_square_bonus(Side::White, PieceType::King, (6, 0)),
+        TaperedPieceSquareEvaluator::default().get_square_bonus(Side::White, PieceType::King, (6, 0), MAX_PHASE)
+    );
+}
+
+
+#[test]
+fn mobility_and_king_safety() {
+    // the Bishop on c4 is free to move but the one on f8 is stuck behind its pawns
+    let position = board("4kb2/4p1p1/8/8/2B5/8/8/4K3 w - - 0 1");
+    assert!(MobilityEvaluator.evaluate(&position, Side::White) > 0);
+    // both Kings have castled but Black's pawn shield is gone
+    let position = board("r5k1/8/8/8/8/8/5PPP/5RK1 w - - 0 1");
+    assert_eq!(KingSafetyEvaluator.evaluate(&position, Side::White), 30);
+}
+
+
+#[test]
+fn weighted_evaluators_add_up() {
+    let position = board("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+    let evaluator = WeightedEvaluator::new()
+        .with(0.5, MaterialEvaluator)
+        .with(2.0, PieceSquareEvaluator);
+    let expected = 150 + 2 * PieceSquareEvaluator.evaluate(&position, Side::White);
+    assert_eq!(evaluator.evaluate(&position, Side::White), expected);
+    assert_eq!(WeightedEvaluator::new().evaluate(&position, Side::White), 0);
+}
+
+
+#[test]
+fn searcher_uses_the_given_evaluator() {
+    // with only material to go on every quiet move scores the same, but the piece square tables prefer centralizing the Knight
+    let position = board("4k3/8/8/8/8/8/P7/N3K3 w - - 0 1");
+    let limits = SearchLimits { max_depth: 1, time_limit: None };
+    let material = Searcher::new(Side::White, limits).evaluator(MaterialEvaluator).search(&position);
+    assert_eq!(material.score, 400);
+    let placement = Searcher::new(Side::White, limits).evaluator(PieceSquareEvaluator).search(&position);
+    assert_eq!(placement.best_move.unwrap().to_uci(), "a1b3");
+}
+
+
+#[test]
+fn tapered_tables_follow_the_game_phase() {
+    assert_eq!(TaperedPieceSquareEvaluator::get_phase(&ChessBoard::new()), MAX_PHASE);
+    assert_eq!(TaperedPieceSquareEvaluator::get_phase(&board("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1")), 0);
+    assert_eq!(TaperedPieceSquareEvaluator::get_phase(&board("3qk3/8/8/8/8/8/8/1N2K2R w - - 0 1")), 7);
+
+    // the King should hide while there are pieces around, but come to the center once they're gone
+    let tables = TaperedPieceSquareEvaluator::default();
+    let king_bonus = |position, phase| tables.get_square_bonus(Side::White, PieceType::King, position, phase);
+    assert!(king_bonus((6, 0), MAX_PHASE) > king_bonus((4, 3), MAX_PHASE));
+    assert!(king_bonus((6, 0), 0) < king_bonus((4, 3), 0));
+    assert_eq!(king_bonus((4, 3), MAX_PHASE / 2), (king_bonus((4, 3), MAX_PHASE) + king_bonus((4, 3), 0)) / 2);
+
+    let active_king = board("4k3/8/8/8/4K3/8/4P3/8 w - - 0 1");
+    let back_rank_king = board("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    assert!(tables.evaluate(&active_king, Side::White) > tables.evaluate(&back_rank_king, Side::White));
+}
+
+
+#[test]
+fn tapered_tables_load_from_json() {
+    let from_file = TaperedPieceSquareEvaluator::from_file("data/piece_square_tables.json").unwrap();
+    let position = board("r1bqk2r/pp2bppp/2n1pn2/3p4/3P4/2NBPN2/PP3PPP/R1BQK2R w KQkq - 0 8");
+    assert_eq!(from_file.evaluate(&position, Side::White), TaperedPieceSquareEvaluator::default().evaluate(&position, Side::White));
+
+    // every table is all zeroes except a bonus for a White pawn on e4 in the endgame
+    let zeroes = format!("[{}]", ["[0, 0, 0, 0, 0, 0, 0, 0]"; 8].join(", "));
+    let phase_json = |pawn_table: &str| format!(
+        "{{\"pawn\": {}, \"knight\": {z}, \"bishop\": {z}, \"rook\": {z}, \"queen\": {z}, \"king\": {z}}}", pawn_table, z = zeroes
+    );
+    let mut e4_rows = ["[0, 0, 0, 0, 0, 0, 0, 0]"; 8];
+    e4_rows[4] = "[0, 0, 0, 0, 40, 0, 0, 0]";  // the 4th rank is the 5th row counting from the 8th rank
+    let e4_table = format!("[{}]", e4_rows.join(", "));
+    let json = format!("{{\"middlegame\": {}, \"endgame\": {}}}", phase_json(&zeroes), phase_json(&e4_table));
+    let tables = TaperedPieceSquareEvaluator::from_json(&json).unwrap();
+    assert_eq!(tables.evaluate(&board("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1"), Side::White), 40);
+
+    assert!(TaperedPieceSquareEvaluator::from_json("{}").is_err());
+    assert!(TaperedPieceSquareEvaluator::from_json(&format!("{{\"middlegame\": {}, \"endgame\": {{}}}}", phase_json(&zeroes))).is_err());
+    let short_row = zeroes.replacen("[0, 0, 0, 0, 0, 0, 0, 0]", "[0, 0]", 1);
+    assert!(TaperedPieceSquareEvaluator::from_json(&format!("{{\"middlegame\": {}, \"endgame\": {}}}", phase_json(&short_row), phase_json(&zeroes))).is_err());
+    let error = TaperedPieceSquareEvaluator::from_file("data/no_such_tables.json").unwrap_err();
+    assert!(error.to_string().contains("no_such_tables.json"), "{}", error);
+}
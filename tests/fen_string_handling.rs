@@ -1,17 +1,17 @@
-use chessbot_lib::gamelogic::board::ChessBoard;
+use chessbot_lib::gamelogic::{board::ChessBoard, validation::PositionProblem, ChessError, Side};
 
 #[test]
 fn starting_position_fen_parsed_correctly() {
     let fen_board = ChessBoard::from_forsyth_edwards("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0".to_string());
     assert!(fen_board.is_ok());
     let default_board = ChessBoard::new();
-    assert_eq!(default_board.get_board_state_hash(), fen_board.unwrap().get_board_state_hash())
+    assert_eq!(default_board.zobrist_key(), fen_board.unwrap().zobrist_key())
 }
 
 #[test]
 fn default_board_fen_correct_conversion() {
     let default_board = ChessBoard::new();
-    assert_eq!(default_board.to_forsyth_edwards(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0".to_string());
+    assert_eq!(default_board.to_forsyth_edwards(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
 }
 
 #[test]
@@ -62,12 +62,52 @@ fn fen_string_en_passant() {
 fn fen_string_parse_1() {
     let board = ChessBoard::from_forsyth_edwards("r1b1kbnr/1ppp1p1p/p1n3p1/4p3/2Q1P1Pq/7N/PPPP1P1P/RNB1KB1R w KQkq - 0 0".to_string());
     assert!(board.is_ok());
-    assert_eq!(board.unwrap().get_board_state_hash(), 9595281602058382660)
+    let board = board.unwrap();
+    let reparsed = ChessBoard::from_forsyth_edwards(board.to_forsyth_edwards()).unwrap();
+    assert_eq!(board.zobrist_key(), reparsed.zobrist_key())
 }
 
 #[test]
 fn fen_string_parse_2() {
     let board = ChessBoard::from_forsyth_edwards("1r2k1r1/1p5p/2pp2pn/p1b1p3/2PnP1b1/NB1Q2p1/PP1P3q/R1B1K3 b - - 0 0".to_string());
     assert!(board.is_ok());
-    assert_eq!(board.unwrap().get_board_state_hash(), 15171370747527475893)
-}
\ No newline at end of file
+    let board = board.unwrap();
+    let reparsed = ChessBoard::from_forsyth_edwards(board.to_forsyth_edwards()).unwrap();
+    assert_eq!(board.zobrist_key(), reparsed.zobrist_key())
+}
+
+#[test]
+fn fen_string_move_clocks_round_trip() {
+    let fen = "4k3/8/8/8/8/8/8/R3K3 b - - 37 52";
+    let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    assert_eq!(board.state.halfmove_clock, 37);
+    assert_eq!(board.state.fullmove_number, 52);
+    assert_eq!(board.to_forsyth_edwards(), fen)
+}
+
+#[test]
+fn strict_fen_rejects_empty_board() {
+    let board = ChessBoard::from_forsyth_edwards("8/8/8/8/8/8/8/8 w - - 0 0".to_string()).unwrap();
+    assert_eq!(board.validate(), vec![PositionProblem::MissingKing(Side::White), PositionProblem::MissingKing(Side::Black)]);
+    let strict_board = ChessBoard::from_forsyth_edwards_strict("8/8/8/8/8/8/8/8 w - - 0 0".to_string());
+    assert!(matches!(strict_board, Err(ChessError::InvalidState(_))));
+}
+
+#[test]
+fn validate_finds_problems() {
+    let problems = |fen: &str| ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap().validate();
+    assert!(problems("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_empty());
+    assert!(problems("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3").is_empty());
+    assert_eq!(problems("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), vec![PositionProblem::TooManyKings(Side::White)]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"), vec![PositionProblem::PawnOnLastRow((0, 0))]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/4K2r b - - 0 1"), vec![PositionProblem::OpponentInCheck(Side::White)]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/4K3 w K - 0 1"), vec![PositionProblem::CastlingWithoutRook { side: Side::White, kingside: true }]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/R2K4 w Q - 0 1"), vec![PositionProblem::CastlingWithoutKing(Side::White)]);
+    assert_eq!(problems("4k3/8/8/8/8/8/8/4K3 b - e3 0 1"), vec![PositionProblem::InvalidEnPassant(4)]);
+}
+
+#[test]
+fn strict_fen_accepts_valid_positions() {
+    assert!(ChessBoard::from_forsyth_edwards_strict("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string()).is_ok());
+    assert!(ChessBoard::from_forsyth_edwards_960_strict("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9".to_string()).is_ok());
+}
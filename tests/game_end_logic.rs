@@ -0,0 +1,120 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, name_to_index_pair, GameEnd, GameEndReason, Side};
+
+fn play(board: &mut ChessBoard, from: &str, to: &str) {
+    let piece = board.get_square_by_name(from.to_string()).unwrap().unwrap();
+    let the_move = piece.get_specific_move(board, name_to_index_pair(to.to_string()).unwrap()).unwrap();
+    board.perform_move_and_record(&the_move).unwrap();
+}
+
+#[test]
+fn move_clocks_follow_the_game() {
+    let mut board = ChessBoard::new();
+    play(&mut board, "g1", "f3");
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (1, 1));
+    play(&mut board, "g8", "f6");
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (2, 2));
+    // pawn moves reset the halfmove clock
+    play(&mut board, "e2", "e4");
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (0, 2));
+    play(&mut board, "b8", "c6");
+    play(&mut board, "f1", "c4");
+    assert_eq!(board.state.halfmove_clock, 2);
+    // as do captures
+    play(&mut board, "f6", "e4");
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (0, 4));
+}
+
+#[test]
+fn unmake_restores_move_clocks() {
+    let mut board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 b - - 37 52".to_string()).unwrap();
+    let the_move = board.get_all_moves(board.state.current_turn).remove(0);
+    let undo = board.make_move(&the_move).unwrap();
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (38, 53));
+    board.unmake_move(undo);
+    assert_eq!((board.state.halfmove_clock, board.state.fullmove_number), (37, 52));
+}
+
+#[test]
+fn fifty_move_rule_is_a_draw() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 w - - 99 80".to_string()).unwrap();
+    assert!(board.is_game_over(board.state.current_turn).is_none());
+
+    let mut board = board;
+    play(&mut board, "a1", "a2");
+    assert_eq!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::FiftyMoveRule)));
+}
+
+#[test]
+fn seventy_five_move_rule_is_a_draw() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 w - - 150 100".to_string()).unwrap();
+    assert_eq!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::SeventyFiveMoveRule)));
+}
+
+#[test]
+fn checkmate_and_stalemate_are_distinct() {
+    let mated = ChessBoard::from_forsyth_edwards("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1".to_string()).unwrap();
+    assert_eq!(mated.is_game_over(mated.state.current_turn), Some(GameEnd::WhiteVictory(GameEndReason::Checkmate)));
+    let stalemated = ChessBoard::from_forsyth_edwards("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1".to_string()).unwrap();
+    assert_eq!(stalemated.is_game_over(stalemated.state.current_turn), Some(GameEnd::Draw(GameEndReason::Stalemate)));
+}
+
+#[test]
+fn bare_kings_are_insufficient_material() {
+    let board = ChessBoard::from_forsyth_edwards("7k/8/8/8/8/8/8/K7 w - - 0 1".to_string()).unwrap();
+    assert_eq!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::InsufficientMaterial)));
+}
+
+#[test]
+fn fivefold_repetition_is_a_draw() {
+    let mut board = ChessBoard::new();
+    for _ in 0..4 {
+        play(&mut board, "g1", "f3");
+        play(&mut board, "g8", "f6");
+        play(&mut board, "f3", "g1");
+        play(&mut board, "f6", "g8");
+    }
+    assert_eq!(board.get_repetition_count(), 5);
+    assert_eq!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::FivefoldRepetition)));
+}
+
+#[test]
+fn insufficient_material_cases() {
+    let is_insufficient = |fen: &str| ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap().is_insufficient_material();
+    assert!(is_insufficient("7k/8/8/8/8/8/8/KB6 w - - 0 1"));  // K+B vs K
+    assert!(is_insufficient("7k/8/8/8/8/8/8/KN6 w - - 0 1"));  // K+N vs K
+    assert!(is_insufficient("6bk/8/8/8/8/8/8/KB6 w - - 0 1"));  // K+B vs K+B on the same colour
+    assert!(is_insufficient("B5bk/8/2B5/8/8/8/8/KB6 w - - 0 1"));  // any number of bishops all on the same colour
+    assert!(!is_insufficient("5b1k/8/8/8/8/8/8/KB6 w - - 0 1"));  // bishops on opposite colours
+    assert!(!is_insufficient("6nk/8/8/8/8/8/8/KB6 w - - 0 1"));  // K+B vs K+N
+    assert!(!is_insufficient("6nk/8/8/8/8/8/8/KN6 w - - 0 1"));  // K+N vs K+N
+    assert!(!is_insufficient("7k/8/8/8/8/8/8/KNN5 w - - 0 1"));  // K+N+N vs K can be helpmated
+    assert!(!is_insufficient("7k/8/8/8/8/8/P7/K7 w - - 0 1"));
+}
+
+#[test]
+fn can_checkmate_per_side() {
+    // a lone king can't win on time, so the side with the rook flagging is a draw
+    let board = ChessBoard::from_forsyth_edwards("7k/8/8/8/8/8/8/KR6 w - - 0 1".to_string()).unwrap();
+    assert!(board.can_checkmate(Side::White));
+    assert!(!board.can_checkmate(Side::Black));
+    assert_eq!(board.get_timeout_result(Side::White), GameEnd::Draw(GameEndReason::InsufficientMaterial));
+    assert_eq!(board.get_timeout_result(Side::Black), GameEnd::WhiteVictory(GameEndReason::Timeout));
+    // a knight can mate if the opponent has a rook to block their own king in, but not a queen
+    let board = ChessBoard::from_forsyth_edwards("6rk/8/8/8/8/8/8/KN6 w - - 0 1".to_string()).unwrap();
+    assert!(board.can_checkmate(Side::White));
+    let board = ChessBoard::from_forsyth_edwards("6qk/8/8/8/8/8/8/KN6 w - - 0 1".to_string()).unwrap();
+    assert!(!board.can_checkmate(Side::White));
+}
+
+#[test]
+fn blocked_pawn_fortress_is_dead() {
+    let board = ChessBoard::from_forsyth_edwards("8/2k5/8/p1p1p1p1/P1P1P1P1/8/3K4/8 w - - 0 1".to_string()).unwrap();
+    assert!(board.is_blocked_pawn_fortress());
+    assert_eq!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::DeadPosition)));
+    // the a-file is open, so the White king can walk around and take the c5 pawn
+    let board = ChessBoard::from_forsyth_edwards("8/2k5/8/2p1p1p1/2P1P1P1/8/3K4/8 w - - 0 1".to_string()).unwrap();
+    assert!(!board.is_blocked_pawn_fortress());
+    // pawns which can capture aren't locked
+    let board = ChessBoard::from_forsyth_edwards("8/2k5/8/p1pp2p1/P1PP2P1/8/3K4/8 w - - 0 1".to_string()).unwrap();
+    assert!(!board.is_blocked_pawn_fortress());
+}
@@ -0,0 +1,66 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pieces::PieceType, name_to_index_pair, MoveType};
+
+/// Make and then unmake every legal move, checking the board is restored exactly each time
+fn assert_all_moves_restore(fen: &str) {
+    let mut board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    let original_fen = board.to_forsyth_edwards();
+    let original_key = board.zobrist_key();
+    let original_repetitions = board.get_repetition_count();
+    for the_move in board.get_all_moves(board.state.current_turn) {
+        let undo = board.make_move(&the_move).unwrap();
+        assert_eq!(board.move_list.len(), 1);
+        assert_eq!(board.get_repetition_count(), 1);
+        board.unmake_move(undo);
+        assert_eq!(board.to_forsyth_edwards(), original_fen, "board not restored after {:?}", the_move);
+        assert_eq!(board.zobrist_key(), original_key, "key not restored after {:?}", the_move);
+        assert_eq!(board.get_repetition_count(), original_repetitions);
+        assert!(board.move_list.is_empty());
+    }
+}
+
+#[test]
+fn unmake_restores_castling_and_captures() {
+    assert_all_moves_restore("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+}
+
+#[test]
+fn unmake_restores_en_passant() {
+    assert_all_moves_restore("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+}
+
+#[test]
+fn unmake_restores_promotion() {
+    assert_all_moves_restore("1r2k3/2P5/8/8/8/8/5p2/4K1N1 b - - 0 1");
+}
+
+#[test]
+fn unmake_restores_promoted_pawn() {
+    let mut board = ChessBoard::from_forsyth_edwards("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+    let pawn = board.get_square_by_name("c7".to_string()).unwrap().unwrap();
+    let promotion = pawn.get_specific_move(&board, name_to_index_pair("b8".to_string()).unwrap()).unwrap();
+    assert_eq!(promotion.move_type, MoveType::Promotion(PieceType::Queen));
+
+    let undo = board.make_move(&promotion).unwrap();
+    assert_eq!(board.get_square_by_name("b8".to_string()).unwrap().unwrap().piece_type, PieceType::Queen);
+    board.unmake_move(undo);
+    assert_eq!(board.get_square_by_name("c7".to_string()).unwrap().unwrap().piece_type, PieceType::Pawn);
+    assert_eq!(board.get_square_by_name("b8".to_string()).unwrap().unwrap().piece_type, PieceType::Rook);
+}
+
+#[test]
+fn unmake_in_reverse_order_restores_game() {
+    let mut board = ChessBoard::new();
+    let start_fen = board.to_forsyth_edwards();
+    let mut undos = Vec::new();
+    for _ in 0..6 {
+        let the_move = board.get_all_moves(board.state.current_turn).remove(0);
+        undos.push(board.make_move(&the_move).unwrap());
+    }
+    assert_eq!(board.move_list.len(), 6);
+    while let Some(undo) = undos.pop() {
+        board.unmake_move(undo);
+    }
+    assert_eq!(board.to_forsyth_edwards(), start_fen);
+    assert_eq!(board.zobrist_key(), ChessBoard::new().zobrist_key());
+    assert_eq!(board.get_repetition_count(), 1);
+}
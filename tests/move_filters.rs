@@ -0,0 +1,81 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, ChessMove, MoveFilter};
+use chessbot_lib::stratagems::{evaluation::MaterialEvaluator, searcher::{quiescence, SearchLimits, Searcher}};
+
+use itertools::Itertools;
+
+
+// positions with promotions, en passant, castling, and discovered checks
+const POSITIONS: [&str; 5] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "4k3/8/8/2pP4/8/8/8/R2BK2R w K c6 0 1",
+];
+
+
+fn uci_moves(moves: Vec<ChessMove>) -> Vec<String> {
+    moves.iter().map(|m| m.to_uci()).sorted().collect()
+}
+
+fn gives_check(board: &ChessBoard, the_move: &ChessMove) -> bool {
+    let mut after_move = board.clone_without_history();
+    let undo = after_move.make_move(the_move).unwrap();
+    after_move.is_checked(!undo.moved_piece.side)
+}
+
+
+#[test]
+fn filtered_moves_match_filtering_all_moves() {
+    for fen in POSITIONS {
+        let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+        let side = board.state.current_turn;
+        let all_moves = board.get_all_moves(side);
+        let expect = |keep: &dyn Fn(&ChessMove) -> bool| uci_moves(all_moves.iter().filter(|m| keep(m)).cloned().collect());
+
+        assert_eq!(uci_moves(board.get_filtered_moves(side, MoveFilter::All)), expect(&|_| true), "{}", fen);
+        assert_eq!(uci_moves(board.get_filtered_moves(side, MoveFilter::Captures)), expect(&|m| m.captures.is_some()), "{}", fen);
+        assert_eq!(
+            uci_moves(board.get_filtered_moves(side, MoveFilter::CapturesAndPromotions)),
+            expect(&|m| m.captures.is_some() || m.promotion_piece().is_some()),
+            "{}", fen
+        );
+        assert_eq!(
+            uci_moves(board.get_filtered_moves(side, MoveFilter::Quiet)),
+            expect(&|m| m.captures.is_none() && m.promotion_piece().is_none()),
+            "{}", fen
+        );
+        assert_eq!(uci_moves(board.get_filtered_moves(side, MoveFilter::Checks)), expect(&|m| gives_check(&board, m)), "{}", fen);
+    }
+}
+
+
+#[test]
+fn checking_moves_include_discovered_checks_and_castling() {
+    // moving the Bishop off the f file uncovers the Rook
+    let board = ChessBoard::from_forsyth_edwards("5k2/8/8/8/8/5B2/8/4KR2 w - - 0 1".to_string()).unwrap();
+    let checks = uci_moves(board.get_filtered_moves(board.state.current_turn, MoveFilter::Checks));
+    assert!(checks.contains(&"f3c6".to_string()), "{:?}", checks);
+    assert!(!checks.contains(&"f1g1".to_string()), "{:?}", checks);
+    // castling puts the Rook on f1
+    let board = ChessBoard::from_forsyth_edwards("5k2/8/8/8/8/8/8/4K2R w K - 0 1".to_string()).unwrap();
+    let checks = uci_moves(board.get_filtered_moves(board.state.current_turn, MoveFilter::Checks));
+    assert_eq!(checks, vec!["e1g1", "h1f1", "h1h8"]);
+}
+
+
+#[test]
+fn quiescence_resolves_captures() {
+    // taking the pawn on d6 loses the Queen to cxd6, so it's no better than doing nothing
+    let mut board = ChessBoard::from_forsyth_edwards("4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1".to_string()).unwrap();
+    let mut nodes = 0;
+    assert_eq!(quiescence(&mut board, -100_000, 100_000, 0, &MaterialEvaluator, &mut nodes), 700);
+    // but the Knight on d6 is free
+    let mut board = ChessBoard::from_forsyth_edwards("4k3/8/3n4/8/8/8/8/3QK3 w - - 0 1".to_string()).unwrap();
+    assert_eq!(quiescence(&mut board, -100_000, 100_000, 0, &MaterialEvaluator, &mut nodes), 900);
+
+    // a one ply search sees the recapture too
+    let board = ChessBoard::from_forsyth_edwards("4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1".to_string()).unwrap();
+    let result = Searcher::new(board.state.current_turn, SearchLimits { max_depth: 1, time_limit: None }).search(&board);
+    assert_ne!(result.best_move.unwrap().to_uci(), "d1d6");
+}
@@ -0,0 +1,51 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, name_to_index_pair, Side};
+
+fn squares(names: &[&str]) -> Vec<(usize, usize)> {
+    names.iter().map(|n| name_to_index_pair(n.to_string()).unwrap()).collect()
+}
+
+#[test]
+fn starting_position_structure() {
+    let board = ChessBoard::new();
+    let white = board.analyze_pawns(Side::White);
+    assert!(white.passed.is_empty() && white.isolated.is_empty() && white.doubled.is_empty() && white.backward.is_empty());
+    assert_eq!(white.connected.len(), 8);
+    assert_eq!(white.islands, vec![vec![0, 1, 2, 3, 4, 5, 6, 7]]);
+    assert!(white.open_files.is_empty() && white.half_open_files.is_empty() && white.outposts.is_empty());
+}
+
+#[test]
+fn passed_isolated_and_doubled_pawns() {
+    // White: a2 b3 b4 d4 g5 h2, Black: c7 f7 g7 h7
+    let board = ChessBoard::from_forsyth_edwards("4k3/2p2ppp/8/6P1/1P1P4/1P6/P6P/4K3 w - - 0 1".to_string()).unwrap();
+    let white = board.analyze_pawns(Side::White);
+    assert_eq!(white.passed, squares(&["a2"]));  // the c7 pawn can still stop the b pawns
+    assert_eq!(white.isolated, squares(&["d4"]));
+    assert_eq!(white.doubled, squares(&["b3", "b4"]));
+    assert_eq!(white.connected, squares(&["a2", "b3"]));
+    assert_eq!(white.islands, vec![vec![0, 1], vec![3], vec![6, 7]]);
+    assert_eq!(white.open_files, vec![4]);
+    assert_eq!(white.half_open_files, vec![2, 5]);
+
+    let black = board.analyze_pawns(Side::Black);
+    assert!(black.passed.is_empty());
+    assert_eq!(black.isolated, squares(&["c7"]));
+    assert_eq!(black.connected, squares(&["f7", "g7", "h7"]));
+    assert_eq!(black.half_open_files, vec![0, 1, 3]);
+}
+
+#[test]
+fn backward_pawn_and_outpost() {
+    // the e5 pawn is ahead of d6 so it can't defend it, and d5 is covered by White's e4 pawn
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/3p4/4p3/4P3/2P5/8/4K3 w - - 0 1".to_string()).unwrap();
+    let black = board.analyze_pawns(Side::Black);
+    assert_eq!(black.backward, squares(&["d6"]));
+    assert_eq!(black.connected, squares(&["e5", "d6"]));
+    // d4 is defended by c3 but the e5 pawn attacks it
+    let white = board.analyze_pawns(Side::White);
+    assert_eq!(white.outposts, squares(&["b4", "d5", "f5"]));
+    assert!(white.to_string().contains("Outposts: b4 d5 f5"));
+    // with a pawn on c6 as well, d6 could safely advance
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/2pp4/4p3/4P3/2P5/8/4K3 w - - 0 1".to_string()).unwrap();
+    assert!(board.analyze_pawns(Side::Black).backward.is_empty());
+}
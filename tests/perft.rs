@@ -0,0 +1,113 @@
+use chessbot_lib::gamelogic::board::ChessBoard;
+
+/// Check the perft node counts for each depth, starting from depth 1
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    for (depth, nodes) in expected.iter().enumerate() {
+        assert_eq!(board.perft(depth + 1), *nodes, "perft({}) is wrong for {}", depth + 1, fen);
+    }
+    // the board must be left untouched
+    assert_eq!(board.to_forsyth_edwards(), ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap().to_forsyth_edwards());
+}
+
+/// Check the perft node count for a single depth
+fn assert_perft_depth(fen: &str, depth: usize, nodes: u64) {
+    let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    assert_eq!(board.perft(depth), nodes, "perft({}) is wrong for {}", depth, fen);
+}
+
+#[test]
+fn perft_start_position() {
+    assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902]);
+}
+
+#[test]
+fn perft_kiwipete() {
+    assert_perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]);
+}
+
+#[test]
+fn perft_position_3() {
+    assert_perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn perft_position_4() {
+    assert_perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]);
+}
+
+#[test]
+fn perft_position_5() {
+    assert_perft("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379]);
+}
+
+#[test]
+fn perft_position_6() {
+    assert_perft("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[46, 2079, 89890]);
+}
+
+#[test]
+fn perft_en_passant_edge_cases() {
+    // capturing en passant would expose the king along the rank
+    assert_perft("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1", &[6]);
+    // en passant is the only way out of check from the double advanced pawn
+    assert_perft("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", &[9]);
+}
+
+#[test]
+fn perft_promotion_edge_cases() {
+    // promoting to give check
+    assert_perft_depth("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", 6, 217342);
+    // underpromoting to give check
+    assert_perft_depth("8/P1k5/K7/8/8/8/8/8 w - - 0 1", 6, 92683);
+    // self stalemate
+    assert_perft_depth("K1k5/8/P7/8/8/8/8/8 w - - 0 1", 6, 2217);
+}
+
+#[test]
+fn perft_checkmate_and_stalemate() {
+    assert_perft_depth("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", 4, 23527);
+}
+
+/*
+The rest of the tricky positions from https://www.chessprogramming.org/Perft_Results need too many nodes to run quickly in a debug build,
+so run them with `cargo test --release -- --ignored`
+*/
+
+#[test]
+#[ignore]
+fn perft_en_passant_pins() {
+    assert_perft_depth("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1134888);
+    assert_perft_depth("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1015133);
+    assert_perft_depth("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1440467);
+}
+
+#[test]
+#[ignore]
+fn perft_castling_rights() {
+    // castling gives check
+    assert_perft_depth("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 6, 661072);
+    assert_perft_depth("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", 6, 803711);
+    // rights are lost when the rooks are captured
+    assert_perft_depth("r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", 4, 1274206);
+    // castling through check is prevented
+    assert_perft_depth("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 4, 1720476);
+}
+
+#[test]
+#[ignore]
+fn perft_checks() {
+    // promoting out of check
+    assert_perft_depth("2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", 6, 3821001);
+    // discovered check
+    assert_perft_depth("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", 5, 1004658);
+    assert_perft_depth("8/k1P5/8/1K6/8/8/8/8 w - - 0 1", 7, 567584);
+}
+
+#[test]
+fn perft_divide_matches_perft() {
+    let board = ChessBoard::from_forsyth_edwards("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1".to_string()).unwrap();
+    let divided = board.perft_divide(2);
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), board.perft(2));
+}
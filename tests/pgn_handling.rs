@@ -0,0 +1,98 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pgn::{parse_pgn, PgnGame}, GameEnd, GameEndReason};
+
+fn play_san(board: &mut ChessBoard, moves: &[&str]) {
+    for san in moves {
+        let the_move = board.parse_san(san).unwrap();
+        board.perform_move_and_record(&the_move).unwrap();
+    }
+}
+
+#[test]
+fn export_game_with_seven_tag_roster() {
+    let mut board = ChessBoard::new();
+    play_san(&mut board, &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+    let game_end = board.is_game_over(board.state.current_turn).unwrap();
+    let mut game = PgnGame::from_board(&board, Some(&game_end));
+    game.set_tag("White", "Chess \"Bot\"");
+    let pgn = game.to_pgn().unwrap();
+
+    let expected = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Chess \\\"Bot\\\"\"]\n[Black \"?\"]\n[Result \"1-0\"]\n[Termination \"normal\"]\n\n\
+                    1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n";
+    assert_eq!(pgn, expected);
+}
+
+#[test]
+fn export_game_from_custom_position() {
+    let mut board = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/R3K3 b - - 0 40".to_string()).unwrap();
+    play_san(&mut board, &["Kd7", "Ra7+"]);
+    let game = PgnGame::from_board(&board, None);
+    assert_eq!(game.get_tag("SetUp"), Some("1"));
+    assert_eq!(game.get_tag("FEN"), Some("4k3/8/8/8/8/8/8/R3K3 b - - 0 40"));
+    let pgn = game.to_pgn().unwrap();
+    assert!(pgn.ends_with("\n\n40... Kd7 41. Ra7+ *\n"), "{}", pgn);
+}
+
+#[test]
+fn parse_skips_comments_nags_and_variations() {
+    let pgn = r#"[Event "Test \"Match\""]
+[Site "?"]
+[Result "1/2-1/2"]
+
+% this whole line is ignored
+1. e4 {best by test} e5 $1 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 ; a comment to the end of the line
+3. Bb5!? a6 1/2-1/2
+"#;
+    let games = parse_pgn(pgn).unwrap();
+    assert_eq!(games.len(), 1);
+    let game = &games[0];
+    assert_eq!(game.get_tag("Event"), Some("Test \"Match\""));
+    assert_eq!(game.get_result(), "1/2-1/2");
+    let board = game.replay().unwrap();
+    assert_eq!(board.move_list.len(), 6);
+    assert_eq!(board.to_forsyth_edwards(), "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4");
+}
+
+#[test]
+fn parse_multiple_games() {
+    let pgn = "[Event \"One\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n[Event \"Two\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 w - - 0 1\"]\n\n1. Ra8+ Kd7 *\n\n[Event \"Three\"]\n\n1. d4\n";
+    let games = parse_pgn(pgn).unwrap();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].moves.len(), 4);
+    assert_eq!(games[0].get_result(), "0-1");  // there's no Result tag, so it comes from the moves
+    assert!(matches!(games[0].replay().unwrap().is_game_over(chessbot_lib::gamelogic::Side::White), Some(GameEnd::BlackVictory(GameEndReason::Checkmate))));
+    assert_eq!(games[1].get_tag("Event"), Some("Two"));
+    assert_eq!(games[1].replay().unwrap().to_forsyth_edwards(), "R7/3k4/8/8/8/8/8/4K3 w - - 2 2");
+    assert_eq!(games[2].moves.len(), 1);
+}
+
+#[test]
+fn parse_rejects_illegal_moves() {
+    assert!(parse_pgn("1. e4 e5 2. Ke3 *").is_err());
+    assert!(parse_pgn("1. e4 (1. d4 *").is_err());
+    assert!(parse_pgn("[Event \"Unclosed]\n1. e4 *").is_err());
+}
+
+#[test]
+fn export_then_parse_round_trips() {
+    let mut board = ChessBoard::from_forsyth_edwards("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1".to_string()).unwrap();
+    play_san(&mut board, &["O-O-O", "O-O", "Nxf7", "Rxf7", "d6", "Qxd6", "a3", "b3"]);
+    let pgn = PgnGame::from_board(&board, None).to_pgn().unwrap();
+    let games = parse_pgn(&pgn).unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].moves, board.move_list);
+    assert_eq!(games[0].replay().unwrap().to_forsyth_edwards(), board.to_forsyth_edwards());
+}
+
+#[test]
+fn export_termination_for_game_end_reason() {
+    let mut board = ChessBoard::new();
+    play_san(&mut board, &["d4", "d5"]);
+    let game = PgnGame::from_board(&board, Some(&GameEnd::BlackVictory(GameEndReason::Timeout)));
+    assert_eq!(game.get_result(), "0-1");
+    assert_eq!(game.get_tag("Termination"), Some("time forfeit"));
+    let game = PgnGame::from_board(&board, Some(&GameEnd::Draw(GameEndReason::Agreement)));
+    assert_eq!(game.get_result(), "1/2-1/2");
+    assert_eq!(game.get_tag("Termination"), Some("normal"));
+    let game = PgnGame::from_board(&board, None);
+    assert_eq!(game.get_tag("Termination"), None);
+}
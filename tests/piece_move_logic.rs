@@ -3,53 +3,23 @@ extern crate lazy_static;
 
 use std::sync::{Arc, RwLock};
 
-use chessbot_lib::gamelogic::{board::ChessBoard, pieces::{PieceType, ChessPiece}, index_pair_to_name, MoveType, ChessMove, name_to_index_pair, Side};
-
-
-/*
-Setup a custom board state (all flags set to newgame) to evaluate move logic.
-
-8 ·K···B·R
-7 pp···ppp
-6 ··p····Q
-5 ···ppp··
-4 Qp··B···
-3 p····N·N
-2 ···p·pp·
-1 R·B·K··R
-  abcdefgh
-*/
+use chessbot_lib::gamelogic::{board::ChessBoard, pieces::PieceType, index_pair_to_name, MoveType, ChessMove, name_to_index_pair, Side};
+
+
+// Setup a custom board state to evaluate move logic. Upper case pieces are White, lower case are Black.
 lazy_static! {
     static ref CUSTOM_BOARD: Arc<RwLock<ChessBoard>> = {
-        let mut custom_setup_squares: [[Option<ChessPiece>; 8]; 8] = Default::default();
-        custom_setup_squares[0][0] = Some(ChessPiece { position: (0,0), side: Side::White, piece_type: PieceType::Rook});
-        custom_setup_squares[0][2] = Some(ChessPiece { position: (0,2), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[0][3] = Some(ChessPiece { position: (0,3), side: Side::White, piece_type: PieceType::Queen});
-        custom_setup_squares[0][6] = Some(ChessPiece { position: (0,6), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[1][3] = Some(ChessPiece { position: (1,3), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[1][6] = Some(ChessPiece { position: (1,6), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[1][7] = Some(ChessPiece { position: (1,7), side: Side::Black, piece_type: PieceType::King});
-        custom_setup_squares[2][0] = Some(ChessPiece { position: (2,0), side: Side::White, piece_type: PieceType::Bishop});
-        custom_setup_squares[2][5] = Some(ChessPiece { position: (2,5), side: Side::Black, piece_type: PieceType::Pawn}); // moved 1 down
-        custom_setup_squares[3][1] = Some(ChessPiece { position: (3,1), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[3][4] = Some(ChessPiece { position: (3,4), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[4][0] = Some(ChessPiece { position: (4,0), side: Side::White, piece_type: PieceType::Rook});
-        custom_setup_squares[4][3] = Some(ChessPiece { position: (4,3), side: Side::Black, piece_type: PieceType::Bishop}); // added piece
-        custom_setup_squares[4][4] = Some(ChessPiece { position: (4,4), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[5][1] = Some(ChessPiece { position: (5,1), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[5][2] = Some(ChessPiece { position: (5,2), side: Side::Black, piece_type: PieceType::Knight});
-        custom_setup_squares[5][4] = Some(ChessPiece { position: (5,4), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[5][6] = Some(ChessPiece { position: (5,6), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[5][7] = Some(ChessPiece { position: (5,7), side: Side::Black, piece_type: PieceType::Bishop});
-        custom_setup_squares[6][1] = Some(ChessPiece { position: (6,1), side: Side::White, piece_type: PieceType::Pawn});
-        custom_setup_squares[7][5] = Some(ChessPiece { position: (7,5), side: Side::Black, piece_type: PieceType::Queen});  // moved over
-        custom_setup_squares[6][6] = Some(ChessPiece { position: (6,6), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[7][0] = Some(ChessPiece { position: (7,0), side: Side::White, piece_type: PieceType::King});
-        custom_setup_squares[7][2] = Some(ChessPiece { position: (7,2), side: Side::White, piece_type: PieceType::Knight});  // changed from pawn to Knight
-        custom_setup_squares[7][6] = Some(ChessPiece { position: (7,6), side: Side::Black, piece_type: PieceType::Pawn});
-        custom_setup_squares[7][7] = Some(ChessPiece { position: (7,7), side: Side::Black, piece_type: PieceType::Rook});
-
-        let board = ChessBoard::new_with_squares(custom_setup_squares);
+        let board = ChessBoard::from_diagram("
+            8 ·k···b·r
+            7 pp···ppp
+            6 ··p····q
+            5 ···PpP··
+            4 QP··b···
+            3 P····n·N
+            2 ···P·PP·
+            1 R·B·R··K
+              abcdefgh
+        ").unwrap();
 
         // wrap board in an Arc to send between threads since tests are parallel, and use a RwLock to allow simulataneous reading
         Arc::new(RwLock::new(board))
@@ -142,4 +112,89 @@ fn white_pawn_knight_capture() {
     let target_square_piece = target_square_opt.unwrap();
     assert_eq!(target_square_piece.piece_type, PieceType::Pawn);
     assert_eq!(target_square_piece.side, Side::White);
-}
\ No newline at end of file
+}
+
+#[test]
+fn pawn_promotes_to_every_piece_type() {
+    let board = ChessBoard::from_forsyth_edwards("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+    let pawn = board.get_square_by_name("c7".to_string()).unwrap().unwrap();
+    let moves = pawn.get_moves(&board);
+    // both the advance to c8 and the capture on b8 can become any of the four pieces
+    assert_eq!(moves.len(), 8);
+    for destination in ["b8", "c8"] {
+        let promotions = moves.iter()
+            .filter(|m| m.destination == name_to_index_pair(destination.to_string()).unwrap())
+            .filter_map(|m| m.promotion_piece())
+            .collect::<Vec<PieceType>>();
+        assert_eq!(promotions, [PieceType::Queen, PieceType::Knight, PieceType::Rook, PieceType::Bishop]);
+    }
+}
+
+
+#[test]
+fn knight_underpromotion_gives_check() {
+    let mut board = ChessBoard::from_forsyth_edwards("8/5P1k/8/8/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+    let pawn = board.get_square_by_name("f7".to_string()).unwrap().unwrap();
+    let knight_promotion = pawn.get_moves(&board).into_iter().find(|m| m.promotion_piece() == Some(PieceType::Knight)).unwrap();
+    board.perform_move_and_record(&knight_promotion).unwrap();
+    assert_eq!(board.get_square_by_name("f8".to_string()).unwrap().unwrap().piece_type, PieceType::Knight);
+    assert!(board.is_checked(Side::Black));
+}
+
+
+#[test]
+fn promotion_moves_are_only_equal_with_the_same_piece() {
+    let promote_to = |piece_type| ChessMove {
+        from_square: (4, 6),
+        destination: (4, 7),
+        move_type: MoveType::Promotion(piece_type),
+        captures: None
+    };
+    assert_eq!(promote_to(PieceType::Knight), promote_to(PieceType::Knight));
+    assert_ne!(promote_to(PieceType::Knight), promote_to(PieceType::Queen));
+}
+
+
+#[test]
+fn inconsistent_moves_are_rejected_without_changing_the_board() {
+    let mut board = ChessBoard::new();
+    let fen = board.to_forsyth_edwards();
+    let move_from = |from: &str, to: &str, captures: Option<&str>| ChessMove {
+        from_square: name_to_index_pair(from.to_string()).unwrap(),
+        destination: name_to_index_pair(to.to_string()).unwrap(),
+        move_type: MoveType::Standard,
+        captures: captures.map(|c| name_to_index_pair(c.to_string()).unwrap())
+    };
+
+    let no_piece = board.perform_move(&move_from("e4", "e5", None)).unwrap_err();
+    assert!(no_piece.to_string().contains("no piece on e4"), "{}", no_piece);
+    assert!(board.perform_move(&move_from("a1", "a2", Some("a2"))).is_err());  // onto its own pawn
+    assert!(board.perform_move(&move_from("e2", "e3", Some("e3"))).is_err());  // nothing there to capture
+    assert!(board.make_move(&move_from("e2", "e2", None)).is_err());
+    assert_eq!(board.to_forsyth_edwards(), fen);
+    assert!(board.move_list.is_empty());
+}
+
+
+#[test]
+fn try_apply_only_allows_legal_moves() {
+    let mut board = ChessBoard::new();
+    let move_from = |from: &str, to: &str| ChessMove {
+        from_square: name_to_index_pair(from.to_string()).unwrap(),
+        destination: name_to_index_pair(to.to_string()).unwrap(),
+        move_type: MoveType::Standard,
+        captures: None
+    };
+
+    let wrong_side = board.try_apply(&move_from("e7", "e5")).unwrap_err();
+    assert!(wrong_side.to_string().contains("White's turn"), "{}", wrong_side);
+    assert!(board.try_apply(&move_from("e2", "e5")).is_err());
+    assert!(board.try_apply(&move_from("g1", "g3")).is_err());
+    // the double advance has to be given as one
+    assert!(board.try_apply(&move_from("e2", "e4")).is_err());
+    let double_advance = board.parse_uci("e2e4").unwrap();
+    let undo = board.try_apply(&double_advance).unwrap();
+    assert_eq!(board.move_list, vec![double_advance]);
+    board.unmake_move(undo);
+    assert_eq!(board.to_forsyth_edwards(), ChessBoard::new().to_forsyth_edwards());
+}
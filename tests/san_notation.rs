@@ -0,0 +1,87 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pieces::PieceType, ChessError, MoveType};
+
+fn board_from(fen: &str) -> ChessBoard {
+    ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap()
+}
+
+/// Parse a SAN move and then turn it back into SAN
+fn san_round_trip(board: &ChessBoard, san: &str) -> String {
+    board.parse_san(san).unwrap().to_san(board)
+}
+
+#[test]
+fn pawn_and_piece_moves() {
+    let board = ChessBoard::new();
+    assert_eq!(board.parse_san("e4").unwrap().move_type, MoveType::DoubleAdvance);
+    assert_eq!(san_round_trip(&board, "e3"), "e3");
+    assert_eq!(san_round_trip(&board, "Nf3"), "Nf3");
+    assert!(matches!(board.parse_san("Nf4"), Err(ChessError::InvalidMove(_))));
+    assert!(board.parse_san("Zz9").is_err());
+}
+
+#[test]
+fn disambiguation() {
+    // knights on b1 and f3 can both reach d2
+    let board = board_from("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1");
+    assert_eq!(san_round_trip(&board, "Nbd2"), "Nbd2");
+    assert_eq!(san_round_trip(&board, "Nfd2"), "Nfd2");
+    assert!(matches!(board.parse_san("Nd2"), Err(ChessError::InvalidMove(_))));
+
+    // rooks on a1 and a5 can both reach a3
+    let board = board_from("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+    assert_eq!(san_round_trip(&board, "R1a3"), "R1a3");
+    assert_eq!(san_round_trip(&board, "R5a3"), "R5a3");
+
+    // queens on a1, a3 and c1 can all reach b2
+    let board = board_from("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+    assert_eq!(san_round_trip(&board, "Qa1b2"), "Qa1b2");
+    assert_eq!(san_round_trip(&board, "Qa3b2"), "Q3b2");
+    assert_eq!(san_round_trip(&board, "Qcb2"), "Qcb2");
+    assert!(board.parse_san("Qab2").is_err());
+}
+
+#[test]
+fn captures_and_en_passant() {
+    let board = board_from("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    let en_passant = board.parse_san("exf6").unwrap();
+    assert_eq!(en_passant.move_type, MoveType::EnPassant);
+    assert_eq!(en_passant.to_san(&board), "exf6");
+}
+
+#[test]
+fn castling() {
+    let board = board_from("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(board.parse_san("O-O").unwrap().destination, (6, 0));
+    assert_eq!(board.parse_san("0-0-0").unwrap().destination, (2, 0));
+    assert_eq!(san_round_trip(&board, "O-O-O"), "O-O-O");
+    assert_eq!(san_round_trip(&board, "O-O"), "O-O");
+}
+
+#[test]
+fn promotion_with_check() {
+    let board = board_from("8/5P1k/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(board.parse_san("f8=N+").unwrap().promotion_piece(), Some(PieceType::Knight));
+    assert_eq!(board.parse_san("f8Q").unwrap().promotion_piece(), Some(PieceType::Queen));
+    assert_eq!(san_round_trip(&board, "f8=N"), "f8=N+");
+    assert_eq!(san_round_trip(&board, "f8=R"), "f8=R");
+    assert!(board.parse_san("f8").is_err());
+}
+
+#[test]
+fn checkmate_and_annotations() {
+    let mut board = ChessBoard::new();
+    for san in ["f3", "e5!", "g4??"] {
+        let the_move = board.parse_san(san).unwrap();
+        board.perform_move_and_record(&the_move).unwrap();
+    }
+    assert_eq!(san_round_trip(&board, "Qh4!!"), "Qh4#");
+}
+
+#[test]
+fn every_move_round_trips() {
+    let board = board_from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    for the_move in board.get_all_moves(board.state.current_turn) {
+        let san = the_move.to_san(&board);
+        assert_eq!(board.parse_san(&san).unwrap(), the_move, "{} did not round trip", san);
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use chessbot_lib::{gamelogic::board::ChessBoard, stratagems::searcher::{SearchLimits, Searcher}};
+use chessbot_lib::gamelogic::Side;
+
+
+fn search(fen: &str, max_depth: usize) -> (ChessBoard, chessbot_lib::stratagems::searcher::SearchResult) {
+    let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    let mut searcher = Searcher::new(board.state.current_turn, SearchLimits { max_depth, time_limit: None });
+    let result = searcher.search(&board);
+    (board, result)
+}
+
+
+#[test]
+fn finds_mate_in_one() {
+    let (board, result) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+    assert_eq!(result.best_move.as_ref().unwrap().to_san(&board), "Ra8#");
+    assert_eq!(result.mate_in(), Some(1));
+    assert_eq!(result.depth, 1);  // no need to look deeper once mate is found
+}
+
+
+#[test]
+fn finds_mate_in_two() {
+    // the Queen needs one move to get to g7, which Black has no way of covering
+    let (board, result) = search("6k1/5p1p/5PpB/8/8/8/5Q2/6K1 w - - 0 1", 3);
+    assert_eq!(result.mate_in(), Some(2));
+    assert_eq!(result.principal_variation.len(), 3);
+    let mut after_line = board.clone();
+    for the_move in result.principal_variation.iter() {
+        after_line.make_move(the_move).unwrap();
+    }
+    assert!(after_line.is_checked(Side::Black));
+    assert!(after_line.get_all_moves(Side::Black).is_empty());
+}
+
+
+#[test]
+fn sees_a_knight_fork() {
+    // Nc7+ forks the King and the Rook, which a one move lookahead doesn't see is worth more than anything else
+    let (board, result) = search("r3k3/8/8/3N4/8/8/4P3/4K3 w - - 0 1", 3);
+    assert_eq!(result.best_move.as_ref().unwrap().to_san(&board), "Nc7+");
+    // Black starts a Rook for a Knight and pawn ahead, but ends up with nothing
+    assert!(result.score > 200, "score was {}", result.score);
+}
+
+
+#[test]
+fn avoids_getting_mated() {
+    // Black has to deal with the back rank threat of Re8#
+    let (board, result) = search("6k1/5ppp/8/8/8/8/5PPP/4R1K1 b - - 0 1", 2);
+    let mut after_move = board.clone();
+    after_move.make_move(&result.best_move.unwrap()).unwrap();
+    let mate = after_move.get_all_moves(Side::White).into_iter().find(|m| m.to_san(&after_move).ends_with('#'));
+    assert!(mate.is_none(), "Black allowed {}", mate.unwrap().to_san(&after_move));
+}
+
+
+#[test]
+fn stops_at_the_time_limit() {
+    let board = ChessBoard::new();
+    let mut searcher = Searcher::new(Side::White, SearchLimits { max_depth: 64, time_limit: Some(Duration::from_millis(500)) });
+    let start = Instant::now();
+    let result = searcher.search(&board);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+}
@@ -0,0 +1,59 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, name_to_index_pair};
+
+fn see(fen: &str, san: &str) -> i64 {
+    let board = ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap();
+    let the_move = board.parse_san(san).unwrap();
+    board.static_exchange_eval(&the_move)
+}
+
+#[test]
+fn undefended_pawn_is_won() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "Rxe5"), 1);
+}
+
+#[test]
+fn defended_pawn_loses_the_knight() {
+    assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "Nxe5"), -2);
+}
+
+#[test]
+fn x_ray_attackers_join_the_exchange() {
+    // the Rook behind d2 recaptures, so Black shouldn't take back
+    assert_eq!(see("4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), 1);
+    // but with a Rook behind d7 too Black wins the exchange
+    assert_eq!(see("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), -4);
+    // a Queen behind a Bishop on the diagonal wins the pawn after the Bishop is traded for the Knight
+    assert_eq!(see("4k3/6p1/5n2/8/8/2B5/8/4K3 w - - 0 1", "Bxf6"), 0);
+    assert_eq!(see("4k3/6p1/5n2/8/8/2B5/1Q6/4K3 w - - 0 1", "Bxf6"), 1);
+}
+
+#[test]
+fn quiet_moves_and_special_moves() {
+    assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra7"), 0);
+    assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra8+"), 0);
+    // moving a Knight to a square guarded by a pawn
+    assert_eq!(see("4k3/8/4p3/8/8/8/2N5/4K3 w - - 0 1", "Nd4"), 0);
+    assert_eq!(see("4k3/8/8/4p3/8/8/2N5/4K3 w - - 0 1", "Nd4"), -3);
+    assert_eq!(see("7k/1P6/8/8/8/8/8/K7 w - - 0 1", "b8=Q+"), 8);
+    assert_eq!(see("k7/1P6/8/8/8/8/8/7K w - - 0 1", "b8=Q+"), -1);
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "exd6"), 1);
+}
+
+#[test]
+fn king_cannot_recapture_on_a_defended_square() {
+    // the Queen is defended by the Rook behind it, so the King can't take it back
+    assert_eq!(see("3rk3/8/8/8/8/8/3Q4/3RK3 w - - 0 1", "Qxd8+"), 5);
+    assert_eq!(see("3rk3/8/8/8/8/8/8/3QK3 b - - 0 1", "Rxd1+"), 4);
+}
+
+#[test]
+fn hanging_pieces() {
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/8/3n4/4P3/8/8/Q3K3 w - - 0 1".to_string()).unwrap();
+    let knight = name_to_index_pair("d5".to_string()).unwrap();
+    assert!(board.is_hanging(knight));
+    assert_eq!(board.get_exchange_loss(knight), 3);
+    let board = ChessBoard::from_forsyth_edwards("4k3/8/4p3/3n4/8/8/8/3QK3 w - - 0 1".to_string()).unwrap();
+    assert!(!board.is_hanging(knight));
+    let queen = name_to_index_pair("d1".to_string()).unwrap();
+    assert!(!board.is_hanging(queen));
+}
@@ -0,0 +1,44 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, name_to_index_pair, tactics::Tactic, Side};
+
+fn tactics(fen: &str, side: Side) -> Vec<Tactic> {
+    ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap().find_tactics(side)
+}
+
+fn square(name: &str) -> (usize, usize) {
+    name_to_index_pair(name.to_string()).unwrap()
+}
+
+#[test]
+fn absolute_and_relative_pins() {
+    let found = tactics("4k3/8/2n5/1B6/8/8/8/4K3 w - - 0 1", Side::White);
+    assert_eq!(found, vec![Tactic::Pin { pinner: square("b5"), pinned: square("c6"), target: square("e8"), absolute: true }]);
+    let found = tactics("3qk3/8/8/3n4/8/8/8/3RK3 w - - 0 1", Side::White);
+    assert_eq!(found, vec![Tactic::Pin { pinner: square("d1"), pinned: square("d5"), target: square("d8"), absolute: false }]);
+    // Black doesn't have any tactics here
+    assert!(tactics("3qk3/8/8/3n4/8/8/8/3RK3 w - - 0 1", Side::Black).is_empty());
+}
+
+#[test]
+fn skewer_through_the_king() {
+    let found = tactics("8/6q1/8/8/3k4/8/8/B3K3 b - - 0 1", Side::White);
+    assert_eq!(found, vec![Tactic::Skewer { attacker: square("a1"), front: square("d4"), behind: square("g7") }]);
+}
+
+#[test]
+fn knight_fork() {
+    let found = tactics("r3k3/2N5/8/8/8/8/8/4K3 b - - 0 1", Side::White);
+    assert_eq!(found, vec![Tactic::Fork { attacker: square("c7"), targets: vec![square("a8"), square("e8")] }]);
+    assert_eq!(found[0].to_string(), "c7 forks a8, e8");
+}
+
+#[test]
+fn discovered_attack() {
+    let found = tactics("4k3/8/8/8/8/8/4N3/4R1K1 w - - 0 1", Side::White);
+    assert_eq!(found, vec![Tactic::DiscoveredAttack { blocker: square("e2"), attacker: square("e1"), target: square("e8") }]);
+}
+
+#[test]
+fn overloaded_defender() {
+    let found = tactics("6k1/4q3/8/2b1n3/8/Q7/8/4R2K w - - 0 1", Side::White);
+    assert!(found.contains(&Tactic::OverloadedDefender { defender: square("e7"), defended: vec![square("c5"), square("e5")] }), "{:?}", found);
+}
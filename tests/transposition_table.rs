@@ -0,0 +1,66 @@
+use chessbot_lib::gamelogic::board::ChessBoard;
+use chessbot_lib::stratagems::searcher::{SearchLimits, Searcher};
+use chessbot_lib::stratagems::transposition::{Bound, TranspositionTable};
+
+
+#[test]
+fn stores_and_probes_entries() {
+    let board = ChessBoard::new();
+    let best_move = board.parse_uci("e2e4").unwrap();
+    let mut table = TranspositionTable::new(1);
+    assert!(table.capacity() > 1000);
+
+    assert!(table.probe(board.zobrist_key()).is_none());
+    table.store(board.zobrist_key(), 4, Bound::Exact, 25, Some(best_move.clone()));
+    let entry = table.probe(board.zobrist_key()).unwrap();
+    assert_eq!((entry.depth, entry.bound, entry.score), (4, Bound::Exact, 25));
+    assert_eq!(entry.best_move, Some(best_move.clone()));
+
+    // a result without a best move keeps the one already known
+    table.store(board.zobrist_key(), 5, Bound::Upper, -10, None);
+    assert_eq!(table.probe(board.zobrist_key()).unwrap().best_move, Some(best_move));
+
+    let stats = table.get_stats();
+    assert_eq!((stats.probes, stats.hits, stats.stores, stats.used), (3, 2, 2, 1));
+    assert!(stats.to_string().starts_with("2 hits from 3 probes (66.7%), 2 stores"));
+
+    table.clear();
+    assert!(table.probe(board.zobrist_key()).is_none());
+    assert_eq!(table.get_stats().used, 0);
+}
+
+
+#[test]
+fn deeper_and_newer_entries_are_kept() {
+    // with a single slot every position wants the same one
+    let mut table = TranspositionTable::new(0);
+    assert_eq!(table.capacity(), 1);
+
+    table.store(1, 6, Bound::Exact, 0, None);
+    table.store(2, 3, Bound::Exact, 0, None);
+    assert!(table.probe(1).is_some(), "a shallower result replaced a deeper one from the same search");
+    table.store(2, 6, Bound::Lower, 0, None);
+    assert!(table.probe(2).is_some());
+
+    // results from searches for earlier moves are replaced by anything
+    table.new_search();
+    assert_eq!(table.get_stats().probes, 0);
+    table.store(3, 1, Bound::Upper, 0, None);
+    assert!(table.probe(3).is_some());
+}
+
+
+#[test]
+fn searcher_reuses_results_between_moves() {
+    let board = ChessBoard::from_forsyth_edwards("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3".to_string()).unwrap();
+    let mut searcher = Searcher::new(board.state.current_turn, SearchLimits { max_depth: 2, time_limit: None }).table_size(4);
+    let first = searcher.search(&board);
+    let first_nodes = first.nodes;
+    assert!(searcher.get_table().get_stats().hits > 0);
+
+    // searching the same position again finds the answers already in the table
+    let second = searcher.search(&board);
+    assert_eq!(first.best_move, second.best_move);
+    assert_eq!(first.score, second.score);
+    assert!(second.nodes < first_nodes, "{} nodes after {}", second.nodes, first_nodes);
+}
@@ -0,0 +1,57 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, pieces::PieceType, ChessError, MoveType};
+
+fn board_from(fen: &str) -> ChessBoard {
+    ChessBoard::from_forsyth_edwards(fen.to_string()).unwrap()
+}
+
+#[test]
+fn parse_resolves_move_types() {
+    let board = ChessBoard::new();
+    assert_eq!(board.parse_uci("e2e4").unwrap().move_type, MoveType::DoubleAdvance);
+    assert_eq!(board.parse_uci("g1f3").unwrap().move_type, MoveType::Standard);
+
+    let board = board_from("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    let en_passant = board.parse_uci("e5f6").unwrap();
+    assert_eq!(en_passant.move_type, MoveType::EnPassant);
+    assert_eq!(en_passant.captures, Some((5, 4)));
+
+    let board = board_from("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(board.parse_uci("e1g1").unwrap().move_type, MoveType::Castle);
+    assert_eq!(board.parse_uci("e1c1").unwrap().move_type, MoveType::Castle);
+}
+
+#[test]
+fn parse_promotions() {
+    let board = board_from("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+    let promotion = board.parse_uci("c7b8n").unwrap();
+    assert_eq!(promotion.move_type, MoveType::Promotion(PieceType::Knight));
+    assert_eq!(promotion.captures, Some((1, 7)));
+    assert_eq!(board.parse_uci("c7c8q").unwrap().promotion_piece(), Some(PieceType::Queen));
+    // a promotion must say which piece, and other moves can't have one
+    assert!(board.parse_uci("c7c8").is_err());
+    assert!(board.parse_uci("e1e2q").is_err());
+}
+
+#[test]
+fn illegal_input_is_an_invalid_move() {
+    let board = ChessBoard::new();
+    for uci in ["e2e5", "e7e5", "e3e4", "e2", "z2e4", "e2e4x", "e1g1"] {
+        assert!(matches!(board.parse_uci(uci), Err(ChessError::InvalidMove(_))), "'{}' should not parse", uci);
+    }
+}
+
+#[test]
+fn every_move_round_trips() {
+    let board = board_from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    for the_move in board.get_all_moves(board.state.current_turn) {
+        let uci = the_move.to_uci();
+        let parsed = board.parse_uci(&uci).unwrap();
+        assert_eq!(parsed, the_move, "{} did not round trip", uci);
+        assert_eq!(parsed.move_type, the_move.move_type);
+        assert_eq!(parsed.captures, the_move.captures);
+    }
+    let board = board_from("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+    let uci = board.get_all_moves(board.state.current_turn).into_iter().map(|m| m.to_uci()).collect::<Vec<String>>();
+    assert!(uci.contains(&"c7b8r".to_string()));
+    assert!(uci.contains(&"c7c8b".to_string()));
+}
@@ -0,0 +1,72 @@
+use chessbot_lib::gamelogic::{board::ChessBoard, name_to_index_pair, ChessMove, GameEnd, GameEndReason};
+
+fn play(board: &mut ChessBoard, from: &str, to: &str) {
+    let piece = board.get_square_by_name(from.to_string()).unwrap().unwrap();
+    let the_move: ChessMove = piece.get_specific_move(board, name_to_index_pair(to.to_string()).unwrap()).unwrap();
+    board.perform_move_and_record(&the_move).unwrap();
+}
+
+#[test]
+fn incremental_key_matches_full_calculation() {
+    let mut board = ChessBoard::new();
+    for (from, to) in [("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("g8", "f6"), ("g1", "f3"), ("e7", "e5"), ("d5", "e6"), ("f8", "c5"), ("f1", "e2"), ("e8", "g8")] {
+        play(&mut board, from, to);
+        assert_eq!(board.zobrist_key(), board.compute_zobrist_key(), "key diverged after {}{}", from, to);
+    }
+}
+
+#[test]
+fn transpositions_have_the_same_key() {
+    let mut board_1 = ChessBoard::new();
+    play(&mut board_1, "g1", "f3");
+    play(&mut board_1, "g8", "f6");
+    play(&mut board_1, "b1", "c3");
+
+    let mut board_2 = ChessBoard::new();
+    play(&mut board_2, "b1", "c3");
+    play(&mut board_2, "g8", "f6");
+    play(&mut board_2, "g1", "f3");
+
+    assert_eq!(board_1.zobrist_key(), board_2.zobrist_key());
+}
+
+#[test]
+fn side_to_move_changes_key() {
+    let white_to_move = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+    let black_to_move = ChessBoard::from_forsyth_edwards("4k3/8/8/8/8/8/8/4K3 b - - 0 1".to_string()).unwrap();
+    assert_ne!(white_to_move.zobrist_key(), black_to_move.zobrist_key());
+}
+
+#[test]
+fn castling_rights_change_key() {
+    let can_castle = ChessBoard::from_forsyth_edwards("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string()).unwrap();
+    let cannot_castle = ChessBoard::from_forsyth_edwards("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1".to_string()).unwrap();
+    assert_ne!(can_castle.zobrist_key(), cannot_castle.zobrist_key());
+}
+
+#[test]
+fn en_passant_only_counts_when_capture_is_possible() {
+    // no black pawn can capture on e3, so the position is the same with or without the target square
+    let no_capture = ChessBoard::from_forsyth_edwards("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1".to_string()).unwrap();
+    let no_target = ChessBoard::from_forsyth_edwards("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1".to_string()).unwrap();
+    assert_eq!(no_capture.zobrist_key(), no_target.zobrist_key());
+
+    let capture = ChessBoard::from_forsyth_edwards("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1".to_string()).unwrap();
+    let no_target = ChessBoard::from_forsyth_edwards("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1".to_string()).unwrap();
+    assert_ne!(capture.zobrist_key(), no_target.zobrist_key());
+}
+
+#[test]
+fn threefold_repetition_is_a_draw() {
+    let mut board = ChessBoard::new();
+    for _ in 0..2 {
+        play(&mut board, "g1", "f3");
+        play(&mut board, "g8", "f6");
+        play(&mut board, "f3", "g1");
+        assert!(board.is_game_over(board.state.current_turn).is_none());
+        play(&mut board, "f6", "g8");
+    }
+    // the starting position has now occured 3 times
+    assert_eq!(board.get_repetition_count(), 3);
+    assert!(matches!(board.is_game_over(board.state.current_turn), Some(GameEnd::Draw(GameEndReason::ThreefoldRepetition))));
+}